@@ -0,0 +1,147 @@
+//! Errors that point into a source file.
+//!
+//! A [`Diagnostic`] knows the file and the line and column it is about, and renders as an
+//! annotated snippet of that line:
+//!
+//! ```text
+//! error: Invalid `date`: invalid date "2025-02-30", expected YYYY-MM-DD or a datetime
+//!  --> content/posts/hello.md:3:7
+//!   |
+//! 3 | date: 2025-02-30
+//!   |       ^
+//! ```
+//!
+//! Parsing many files reports every problem at once as [`Diagnostics`], in an
+//! [`Error::Parse`](crate::Error::Parse).
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// A 1-based position in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    /// `None` when the problem is with the line as a whole.
+    pub column: Option<usize>,
+}
+
+impl Location {
+    pub fn line(line: usize) -> Self {
+        Location { line, column: None }
+    }
+
+    pub fn new(line: usize, column: usize) -> Self {
+        Location {
+            line,
+            column: Some(column),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file, if the source came from one.
+    pub path: Option<PathBuf>,
+    pub location: Location,
+    pub message: String,
+    /// The line at [`Self::location`], for the snippet.
+    pub source_line: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(location: Location, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: None,
+            location,
+            message: message.into(),
+            source_line: None,
+        }
+    }
+
+    /// Attach the file the diagnostic is about.
+    pub fn in_file(mut self, path: &Path) -> Self {
+        self.path = Some(path.to_path_buf());
+        self
+    }
+
+    /// Attach the line of `source`, the whole file, that the diagnostic points at.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source_line = source
+            .lines()
+            .nth(self.location.line.saturating_sub(1))
+            .map(str::to_string);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Location { line, column } = self.location;
+        let path = self
+            .path
+            .as_deref()
+            .map_or("<input>".into(), Path::to_string_lossy);
+        writeln!(f, "error: {}", self.message)?;
+
+        let gutter = " ".repeat(line.to_string().len());
+        match column {
+            Some(column) => write!(f, "{gutter}--> {path}:{line}:{column}")?,
+            None => write!(f, "{gutter}--> {path}:{line}")?,
+        }
+        if let Some(source_line) = &self.source_line {
+            write!(f, "\n{gutter} |\n{line} | {source_line}")?;
+            if let Some(column) = column {
+                // Keep tabs so the caret lines up with the text above it.
+                let pad: String = source_line
+                    .chars()
+                    .take(column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                write!(f, "\n{gutter} | {pad}^")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Every diagnostic found in one go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.0 {
+            write!(f, "{diagnostic}\n\n")?;
+        }
+        write!(f, "{} problem(s) found", self.0.len())
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let source = "---\ntitle: Hi\ndate: 2025-02-30\n---\n";
+        let diagnostic = Diagnostic::new(Location::new(3, 7), "Invalid `date`")
+            .in_file(Path::new("content/hello.md"))
+            .with_source(source);
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: Invalid `date`\n --> content/hello.md:3:7\n  |\n3 | date: 2025-02-30\n  |       ^"
+        );
+
+        let diagnostic = Diagnostic::new(Location::line(12), "missing required key `title`");
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: missing required key `title`\n  --> <input>:12"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! The error type of the [`fs`](crate::fs), [`md`](crate::md) and [`html`](crate::html)
+//! modules.
+//!
+//! Nothing in these modules panics on bad input, a missing directory is an [`Error::NotFound`]
+//! the caller can recover from.
+
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::diagnostic::{Diagnostic, Diagnostics};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound(PathBuf),
+    /// Expected a directory, found a file.
+    NotADirectory(PathBuf),
+    /// Expected a file, found a directory.
+    NotAFile(PathBuf),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    /// `child` is a symlink to `ancestor`, one of the directories it is in.
+    SymlinkLoop {
+        ancestor: PathBuf,
+        child: PathBuf,
+    },
+    /// A glob or [ignore file](crate::fs::IGNORE_FILE_NAME) line that does not parse.
+    InvalidPattern(String),
+    /// Source files that do not parse, with every problem found.
+    Parse(Diagnostics),
+    /// A frontmatter value that does not have the type it was read as.
+    InvalidValue {
+        path: PathBuf,
+        key: String,
+        message: String,
+    },
+    Render {
+        path: PathBuf,
+        message: String,
+    },
+    /// Two sources write the same output file.
+    OutputCollision {
+        dest: PathBuf,
+        first: String,
+        second: String,
+    },
+    /// A listing links to a page that is never written.
+    BrokenLink {
+        listing: String,
+        url: String,
+        path: PathBuf,
+    },
+}
+
+impl Error {
+    /// For `map_err`, to tell which path an I/O error is about.
+    pub(crate) fn io(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+        move |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotFound(path) => write!(f, "Path does not exist: {}", path.display()),
+            Error::NotADirectory(path) => {
+                write!(
+                    f,
+                    "Expected a directory, but found a file: {}",
+                    path.display()
+                )
+            }
+            Error::NotAFile(path) => {
+                write!(
+                    f,
+                    "Expected a file, but found a directory: {}",
+                    path.display()
+                )
+            }
+            Error::Io { path, .. } => write!(f, "Failed to access {}", path.display()),
+            Error::SymlinkLoop { ancestor, child } => write!(
+                f,
+                "Symlink loop: {} points back to {}",
+                child.display(),
+                ancestor.display()
+            ),
+            Error::InvalidPattern(message) => write!(f, "Invalid ignore pattern: {message}"),
+            Error::Parse(diagnostics) => write!(f, "{diagnostics}"),
+            Error::InvalidValue { path, key, message } => write!(
+                f,
+                "Invalid frontmatter value for {key:?} in {}: {message}",
+                path.display()
+            ),
+            Error::Render { path, message } => {
+                write!(f, "Failed to render {}: {message}", path.display())
+            }
+            Error::OutputCollision {
+                dest,
+                first,
+                second,
+            } => write!(
+                f,
+                "Output collision: {} is produced by both {first} and {second}",
+                dest.display()
+            ),
+            Error::BrokenLink { listing, url, path } => write!(
+                f,
+                "Broken link in {listing}: {url} ({}) was never emitted",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<Diagnostics> for Error {
+    fn from(diagnostics: Diagnostics) -> Self {
+        Error::Parse(diagnostics)
+    }
+}
+
+impl From<Diagnostic> for Error {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Error::Parse(Diagnostics(vec![diagnostic]))
+    }
+}
@@ -0,0 +1,313 @@
+//! RSS 2.0, Atom and JSON Feed 1.1 feeds.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{html::escape, md::ParsedFile, url::absolute};
+
+/// A feed and its entries, newest first.
+#[derive(Debug, Clone)]
+pub struct Feed {
+    pub title: String,
+    pub description: String,
+    pub author: String,
+    /// Absolute URL of the site, every other URL is resolved against it.
+    pub base_url: String,
+    /// Site-relative URL of the page the feed is about.
+    pub link: String,
+    /// Site-relative URL the feed itself is published at.
+    pub self_url: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub title: String,
+    /// Site-relative URL of the page.
+    pub url: String,
+    pub date: DateTime<Utc>,
+    /// Plain text: the frontmatter summary, or the page's first paragraph.
+    pub summary: Option<String>,
+    /// The rendered HTML of the page, when the feed carries full content.
+    pub content: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl FeedEntry {
+    pub fn new(page: &ParsedFile, content: Option<String>) -> Self {
+        FeedEntry {
+            title: page.title().to_string(),
+            url: page.meta.url.clone(),
+            date: page.date(),
+            summary: page
+                .summary()
+                .map(str::to_string)
+                .or_else(|| page.excerpt()),
+            content,
+            tags: page
+                .contents
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.tags.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Feed {
+    fn updated(&self) -> DateTime<Utc> {
+        self.entries
+            .iter()
+            .map(|entry| entry.date)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn rss(&self) -> String {
+        let mut out = String::new();
+        out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        out.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom"><channel>"#);
+        let _ = write!(
+            out,
+            "<title>{}</title><link>{}</link><description>{}</description>\
+             <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\
+             <lastBuildDate>{}</lastBuildDate>",
+            escape(&self.title),
+            escape(&absolute(&self.base_url, &self.link)),
+            escape(&self.description),
+            escape(&absolute(&self.base_url, &self.self_url)),
+            self.updated().to_rfc2822(),
+        );
+
+        for entry in &self.entries {
+            let url = escape(&absolute(&self.base_url, &entry.url));
+            let _ = write!(
+                out,
+                "<item><title>{}</title><link>{url}</link>\
+                 <guid isPermaLink=\"true\">{url}</guid><pubDate>{}</pubDate>",
+                escape(&entry.title),
+                entry.date.to_rfc2822(),
+            );
+            // Descriptions hold HTML, so the plain text summary is escaped into HTML first.
+            let description = entry
+                .content
+                .clone()
+                .or_else(|| entry.summary.as_deref().map(escape));
+            if let Some(description) = description {
+                let _ = write!(out, "<description>{}</description>", escape(&description));
+            }
+            for tag in &entry.tags {
+                let _ = write!(out, "<category>{}</category>", escape(tag));
+            }
+            out.push_str("</item>");
+        }
+
+        out.push_str("</channel></rss>");
+        out
+    }
+
+    pub fn atom(&self) -> String {
+        let mut out = String::new();
+        out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        out.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        let link = escape(&absolute(&self.base_url, &self.link));
+        let _ = write!(
+            out,
+            "<title>{}</title><subtitle>{}</subtitle><link href=\"{link}\"/>\
+             <link rel=\"self\" href=\"{}\"/><id>{link}</id><updated>{}</updated>",
+            escape(&self.title),
+            escape(&self.description),
+            escape(&absolute(&self.base_url, &self.self_url)),
+            self.updated().to_rfc3339(),
+        );
+        if !self.author.is_empty() {
+            let _ = write!(
+                out,
+                "<author><name>{}</name></author>",
+                escape(&self.author)
+            );
+        }
+
+        for entry in &self.entries {
+            let url = escape(&absolute(&self.base_url, &entry.url));
+            let _ = write!(
+                out,
+                "<entry><title>{}</title><link href=\"{url}\"/><id>{url}</id>\
+                 <published>{date}</published><updated>{date}</updated>",
+                escape(&entry.title),
+                date = entry.date.to_rfc3339(),
+            );
+            if let Some(summary) = &entry.summary {
+                let _ = write!(out, "<summary type=\"text\">{}</summary>", escape(summary));
+            }
+            if let Some(content) = &entry.content {
+                let _ = write!(out, "<content type=\"html\">{}</content>", escape(content));
+            }
+            for tag in &entry.tags {
+                let _ = write!(out, "<category term=\"{}\"/>", escape(tag));
+            }
+            out.push_str("</entry>");
+        }
+
+        out.push_str("</feed>");
+        out
+    }
+
+    /// The feed as [JSON Feed 1.1](https://www.jsonfeed.org/version/1.1/).
+    pub fn json(&self) -> String {
+        let feed = JsonFeed {
+            version: "https://jsonfeed.org/version/1.1",
+            title: &self.title,
+            home_page_url: absolute(&self.base_url, &self.link),
+            feed_url: absolute(&self.base_url, &self.self_url),
+            description: &self.description,
+            authors: if self.author.is_empty() {
+                vec![]
+            } else {
+                vec![JsonAuthor { name: &self.author }]
+            },
+            items: self
+                .entries
+                .iter()
+                .map(|entry| {
+                    let url = absolute(&self.base_url, &entry.url);
+                    JsonItem {
+                        id: url.clone(),
+                        url,
+                        title: &entry.title,
+                        content_html: entry.content.as_deref(),
+                        // Items need some content, the plain text summary stands in when the
+                        // feed has none.
+                        content_text: entry
+                            .content
+                            .is_none()
+                            .then(|| entry.summary.as_deref().unwrap_or("")),
+                        summary: entry.summary.as_deref(),
+                        date_published: entry.date.to_rfc3339(),
+                        tags: &entry.tags,
+                    }
+                })
+                .collect(),
+        };
+        // Only strings and plain structs, which always serialize.
+        serde_json::to_string_pretty(&feed).unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: String,
+    feed_url: String,
+    description: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<JsonAuthor<'a>>,
+    items: Vec<JsonItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonAuthor<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonItem<'a> {
+    id: String,
+    url: String,
+    title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_html: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_text: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<&'a str>,
+    date_published: String,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    tags: &'a [String],
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn feed() -> Feed {
+        Feed {
+            title: "Site & co".into(),
+            description: "A site".into(),
+            author: "me".into(),
+            base_url: "https://example.com/".into(),
+            link: "/".into(),
+            self_url: "/rss.xml".into(),
+            entries: vec![FeedEntry {
+                title: "<Hello>".into(),
+                url: "/hello/".into(),
+                date: "2025-07-29T00:00:00Z".parse().unwrap(),
+                summary: Some("Short".into()),
+                content: Some("<p>Full</p>".into()),
+                tags: vec!["rust".into()],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_rss() {
+        let rss = feed().rss();
+        assert!(rss.contains("<title>Site &amp; co</title>"));
+        assert!(rss.contains("<link>https://example.com/hello/</link>"));
+        assert!(rss.contains("<pubDate>Tue, 29 Jul 2025 00:00:00 +0000</pubDate>"));
+        assert!(rss.contains("<description>&lt;p&gt;Full&lt;/p&gt;</description>"));
+        assert!(rss.contains("<category>rust</category>"));
+
+        let mut summarized = feed();
+        summarized.entries[0].content = None;
+        summarized.entries[0].summary = Some("1 < 2".into());
+        let rss = summarized.rss();
+        assert!(
+            rss.contains("<description>1 &amp;lt; 2</description>"),
+            "{rss}"
+        );
+    }
+
+    #[test]
+    fn test_atom() {
+        let atom = feed().atom();
+        assert!(atom.contains("<title>&lt;Hello&gt;</title>"));
+        assert!(atom.contains(r#"<link rel="self" href="https://example.com/rss.xml"/>"#));
+        assert!(atom.contains("<updated>2025-07-29T00:00:00+00:00</updated>"));
+        assert!(atom.contains(r#"<summary type="text">Short</summary>"#));
+
+        let mut summarized = feed();
+        summarized.entries[0].summary = Some("1 < 2".into());
+        let atom = summarized.atom();
+        assert!(
+            atom.contains(r#"<summary type="text">1 &lt; 2</summary>"#),
+            "{atom}"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json: serde_json::Value = serde_json::from_str(&feed().json()).unwrap();
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(json["feed_url"], "https://example.com/rss.xml");
+        assert_eq!(json["authors"][0]["name"], "me");
+        let item = &json["items"][0];
+        assert_eq!(item["id"], "https://example.com/hello/");
+        assert_eq!(item["content_html"], "<p>Full</p>");
+        assert!(item.get("content_text").is_none());
+        assert_eq!(item["summary"], "Short");
+        assert_eq!(item["date_published"], "2025-07-29T00:00:00+00:00");
+        assert_eq!(item["tags"][0], "rust");
+
+        let mut summarized = feed();
+        summarized.entries[0].content = None;
+        summarized.entries[0].summary = Some("1 < 2".into());
+        let json: serde_json::Value = serde_json::from_str(&summarized.json()).unwrap();
+        let item = &json["items"][0];
+        assert!(item.get("content_html").is_none());
+        assert_eq!(item["content_text"], "1 < 2");
+    }
+}
@@ -1,12 +1,21 @@
 use chrono::{DateTime, Utc};
-use comrak::ComrakOptions;
+
+use ignore::{WalkBuilder, overrides::OverrideBuilder};
 
 use crate::internal_prelude::*;
 use std::path::{Path, PathBuf};
 
+/// Name of the files listing paths for [`list_files_dir_rec`] to skip, in gitignore syntax.
+///
+/// They apply to their own directory and everything below it, like a `.gitignore`, and are
+/// also read from the parents of the scanned directory.
+pub const IGNORE_FILE_NAME: &str = ".aaskaignore";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FileMeta {
     pub path: PathBuf,
+    /// Path relative to the directory that was scanned.
+    pub rel_path: PathBuf,
     pub date: DateTime<Utc>,
     pub file_type: FileType,
 }
@@ -39,15 +48,15 @@ impl From<PathBuf> for FileType {
 }
 
 pub fn list_files_dir(dir: &impl AsRef<Path>) -> Result<Vec<FileMeta>> {
-    utils::assert_dir_exists(dir);
-
     let dir = dir.as_ref();
+    utils::check_dir_exists(dir)?;
+
     let mut res = Vec::new();
-    for entry in dir.read_dir()? {
-        let entry = entry?;
+    for entry in dir.read_dir().map_err(Error::io(dir))? {
+        let entry = entry.map_err(Error::io(dir))?;
         let path = entry.path();
         if path.is_file() {
-            res.push(utils::get_file_meta(&path)?);
+            res.push(utils::get_file_meta(&path, entry.file_name())?);
         }
     }
 
@@ -55,26 +64,121 @@ pub fn list_files_dir(dir: &impl AsRef<Path>) -> Result<Vec<FileMeta>> {
 }
 
 pub fn read_file(path: &impl AsRef<Path>) -> Result<String> {
-    utils::assert_file_exists(path);
     let path = path.as_ref();
-    std::fs::read_to_string(path).wrap_err_with(|| format!("Failed to read file: {:?}", path))
+    utils::check_file_exists(path)?;
+    std::fs::read_to_string(path).map_err(Error::io(path))
+}
+
+/// Copy `src` to `dest`, creating parent directories, unless `dest` already has the same
+/// contents. Returns whether the file was copied.
+///
+/// A destination of the same size that is newer than the source is assumed to be up to date
+/// without reading either file.
+pub fn copy_if_changed(src: &impl AsRef<Path>, dest: &impl AsRef<Path>) -> Result<bool> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+    utils::check_file_exists(src)?;
+
+    if let Ok(dest_meta) = dest.metadata() {
+        let src_meta = src.metadata().map_err(Error::io(src))?;
+        if dest_meta.len() == src_meta.len() {
+            let dest_modified = dest_meta.modified().map_err(Error::io(dest))?;
+            if dest_modified >= src_meta.modified().map_err(Error::io(src))? {
+                return Ok(false);
+            }
+            if std::fs::read(src).map_err(Error::io(src))?
+                == std::fs::read(dest).map_err(Error::io(dest))?
+            {
+                return Ok(false);
+            }
+        }
+    }
+
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent).map_err(Error::io(parent))?;
+    }
+    std::fs::copy(src, dest).map_err(Error::io(dest))?;
+    Ok(true)
+}
+
+/// Which files [`list_files_dir_rec_with`] skips, besides those listed in
+/// [`IGNORE_FILE_NAME`] files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// List files and directories whose name starts with a dot, such as `.DS_Store` or
+    /// `.well-known`. `.git` directories and [`IGNORE_FILE_NAME`] files are skipped either way.
+    pub hidden: bool,
+    /// Names starting with a dot that are listed even without `hidden`, such as
+    /// `.well-known`.
+    pub allow_hidden: Vec<String>,
+    /// Globs, relative to the scanned directory. If any are given, only the files matching
+    /// one of them are listed.
+    pub include: Vec<String>,
+    /// Globs, relative to the scanned directory, of files and directories to skip. They win
+    /// over `include`.
+    pub exclude: Vec<String>,
 }
 
 // traverse a directory recursively and list all files
 pub fn list_files_dir_rec(dir: &impl AsRef<Path>) -> Result<Vec<FileMeta>> {
-    utils::assert_dir_exists(dir);
+    list_files_dir_rec_with(dir, &ScanOptions::default())
+}
 
-    let mut dirs = vec![dir.as_ref().to_path_buf()];
-    let mut files = Vec::new();
-    while let Some(current_dir) = dirs.pop() {
-        for entry in current_dir.read_dir()? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_file() {
-                files.push(utils::get_file_meta(&path)?);
-            } else if path.is_dir() {
-                dirs.push(path.canonicalize()?);
+/// [`list_files_dir_rec`], skipping the files `options` and [`IGNORE_FILE_NAME`] files rule
+/// out. Symlinks are followed, a symlink back to one of its own ancestors is an
+/// [`Error::SymlinkLoop`].
+pub fn list_files_dir_rec_with(
+    dir: &impl AsRef<Path>,
+    options: &ScanOptions,
+) -> Result<Vec<FileMeta>> {
+    let dir = dir.as_ref();
+    utils::check_dir_exists(dir)?;
+
+    let mut overrides = OverrideBuilder::new(dir);
+    // Later globs take precedence, so excludes go last.
+    let globs = options.include.iter().map(|glob| glob.to_string());
+    let globs = globs.chain(options.exclude.iter().map(|glob| format!("!{glob}")));
+    for glob in globs {
+        overrides
+            .add(&glob)
+            .map_err(|e| Error::InvalidPattern(e.to_string()))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| Error::InvalidPattern(e.to_string()))?;
+
+    // Only our own ignore files count, not the ones meant for git. Hidden names are checked
+    // here rather than with `WalkBuilder::hidden`, which has no exceptions.
+    let hidden = options.hidden;
+    let allow_hidden = options.allow_hidden.clone();
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(false)
+        .parents(true)
+        .add_custom_ignore_filename(IGNORE_FILE_NAME)
+        .overrides(overrides)
+        .follow_links(true)
+        .filter_entry(move |entry| {
+            let name = entry.file_name();
+            if entry.depth() == 0 {
+                true
+            } else if name == ".git" || name == IGNORE_FILE_NAME {
+                false
+            } else {
+                hidden
+                    || !name.to_string_lossy().starts_with('.')
+                    || allow_hidden.iter().any(|allowed| name == allowed.as_str())
             }
+        })
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry.map_err(|e| utils::walk_error(dir, e))?;
+        let path = entry.path();
+        if path.is_file() {
+            // Files in symlinked directories keep their path under `dir` here, only
+            // `FileMeta::path` is canonical.
+            let rel_path = path.strip_prefix(dir).unwrap_or(path);
+            files.push(utils::get_file_meta(&path, rel_path)?);
         }
     }
 
@@ -84,44 +188,174 @@ pub fn list_files_dir_rec(dir: &impl AsRef<Path>) -> Result<Vec<FileMeta>> {
 mod utils {
     use super::*;
 
-    pub fn get_file_meta(path: &impl AsRef<Path>) -> Result<FileMeta> {
+    pub fn get_file_meta(
+        path: &impl AsRef<Path>,
+        rel_path: impl Into<PathBuf>,
+    ) -> Result<FileMeta> {
         let path = path.as_ref();
         if !path.exists() {
-            return Err(eyre!("Path does not exist: {:?}", path));
+            return Err(Error::NotFound(path.to_path_buf()));
         }
 
         let file_type = FileType::from(path.to_path_buf());
         let date: DateTime<Utc> = path
-            .metadata()?
-            .modified()
-            .wrap_err(format!("Failed to get modified date for file: {path:?}"))?
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .map_err(Error::io(path))?
             .into();
-        let path = path.to_path_buf().canonicalize()?;
+        let path = path.canonicalize().map_err(Error::io(path))?;
 
         Ok(FileMeta {
             date,
             file_type,
             path,
+            rel_path: rel_path.into(),
         })
     }
 
-    pub fn assert_file_exists(path: &impl AsRef<Path>) {
-        let path = path.as_ref();
+    /// Walk errors are symlink loops, I/O errors or problems with an ignore file or glob.
+    pub fn walk_error(dir: &Path, e: ignore::Error) -> Error {
+        match e {
+            ignore::Error::Loop { ancestor, child } => Error::SymlinkLoop { ancestor, child },
+            ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. }
+                if matches!(*err, ignore::Error::Loop { .. }) =>
+            {
+                walk_error(dir, *err)
+            }
+            e => {
+                let message = e.to_string();
+                match e.into_io_error() {
+                    Some(source) => Error::Io {
+                        path: dir.to_path_buf(),
+                        source,
+                    },
+                    None => Error::InvalidPattern(message),
+                }
+            }
+        }
+    }
+
+    pub fn check_file_exists(path: &Path) -> Result<()> {
         if !path.exists() {
-            panic!("Path does not exist: {path:?}");
+            return Err(Error::NotFound(path.to_path_buf()));
         }
         if !path.is_file() {
-            panic!("Expected a file, but found a directory: {path:?}");
+            return Err(Error::NotAFile(path.to_path_buf()));
         }
+        Ok(())
     }
 
-    pub fn assert_dir_exists(path: &impl AsRef<Path>) {
-        let path = path.as_ref();
+    pub fn check_dir_exists(path: &Path) -> Result<()> {
         if !path.exists() {
-            panic!("Path does not exist: {path:?}");
+            return Err(Error::NotFound(path.to_path_buf()));
         }
         if !path.is_dir() {
-            panic!("Expected a directory, but found a file: {path:?}");
+            return Err(Error::NotADirectory(path.to_path_buf()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_paths() {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+        let missing = manifest_dir.join("does-not-exist");
+        assert!(matches!(list_files_dir_rec(&missing), Err(Error::NotFound(p)) if p == missing));
+        assert!(matches!(read_file(&missing), Err(Error::NotFound(_))));
+
+        let manifest = manifest_dir.join("Cargo.toml");
+        assert!(matches!(
+            list_files_dir(&manifest),
+            Err(Error::NotADirectory(_))
+        ));
+        assert!(matches!(read_file(&manifest_dir), Err(Error::NotAFile(_))));
+    }
+
+    #[test]
+    fn test_scan_filters() {
+        let dir = std::env::temp_dir().join(format!("aaska-scan-{}", std::process::id()));
+        for file in [
+            "post.md",
+            "image.png",
+            ".DS_Store",
+            ".git/config",
+            ".well-known/security.txt",
+            "drafts/wip.md",
+            "node_modules/lib.js",
+        ] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
         }
+        std::fs::write(dir.join(IGNORE_FILE_NAME), "drafts/\n").unwrap();
+
+        let list = |options: &ScanOptions| {
+            let mut files: Vec<_> = list_files_dir_rec_with(&dir, options)
+                .unwrap()
+                .into_iter()
+                .map(|file| file.rel_path.to_string_lossy().into_owned())
+                .collect();
+            files.sort();
+            files
+        };
+        let exclude = ScanOptions {
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let include = ScanOptions {
+            include: vec!["*.md".to_string()],
+            ..Default::default()
+        };
+        let hidden = ScanOptions {
+            hidden: true,
+            ..exclude.clone()
+        };
+        let allow_hidden = ScanOptions {
+            allow_hidden: vec![".well-known".to_string()],
+            ..exclude.clone()
+        };
+        let results = (
+            list(&exclude),
+            list(&include),
+            list(&hidden),
+            list(&allow_hidden),
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.0, ["image.png", "post.md"]);
+        assert_eq!(results.1, ["post.md"]);
+        assert_eq!(
+            results.2,
+            [
+                ".DS_Store",
+                ".well-known/security.txt",
+                "image.png",
+                "post.md"
+            ]
+        );
+        assert_eq!(
+            results.3,
+            [".well-known/security.txt", "image.png", "post.md"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop() {
+        let dir = std::env::temp_dir().join(format!("aaska-loop-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a")).unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("a/loop")).unwrap();
+        let result = list_files_dir_rec(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            matches!(&result, Err(Error::SymlinkLoop { child, .. }) if child.ends_with("a/loop")),
+            "{result:?}"
+        );
     }
 }
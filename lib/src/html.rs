@@ -1,10 +1,14 @@
-use std::path::PathBuf;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use comrak::ComrakOptions;
 
 use crate::{
-    fs::FileMeta,
-    md::{Html, ParsedFile},
+    internal_prelude::*,
+    md::{Body, Html, ParsedFile},
 };
 
 #[derive(Debug)]
@@ -19,17 +23,160 @@ pub struct GeneratedFile {
     pub original_md_path: PathBuf,
     pub meta: GeneratedFileMeta,
 }
-pub fn generate_html(file: &ParsedFile, options: &ComrakOptions) -> GeneratedFile {
-    let mut out = vec![];
-    comrak::format_html(file.contents.body_ast, options, &mut out)
-        .expect("Failed to format HTML from Markdown");
+pub fn generate_html(file: &ParsedFile, options: &ComrakOptions) -> Result<GeneratedFile> {
+    let contents = match &file.contents.body {
+        Body::Markdown(ast) => {
+            let mut out = vec![];
+            comrak::format_html(ast, options, &mut out).map_err(|e| Error::Render {
+                path: file.meta.path.clone(),
+                message: e.to_string(),
+            })?;
+            String::from_utf8_lossy(&out).to_string()
+        }
+        Body::Html(html) => html.clone(),
+        Body::PlainText(text) => format!("<pre>{}</pre>", escape(text)),
+    };
 
-    dbg!(GeneratedFile {
-        contents: Html(String::from_utf8_lossy(&out).to_string()),
+    Ok(GeneratedFile {
+        contents: Html(contents),
         original_md_path: file.meta.path.clone(),
         meta: GeneratedFileMeta {
-            title: "title".into(),
-            description: "desc".into(),
+            title: file.title().to_string(),
+            description: file.description().unwrap_or_default().to_string(),
         },
     })
 }
+
+/// A page that sends browsers on to `url`.
+pub fn redirect(url: &str) -> String {
+    let url = escape(url);
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <link rel=\"canonical\" href=\"{url}\">\
+         <meta http-equiv=\"refresh\" content=\"0; url={url}\"></head>\
+         <body><a href=\"{url}\">{url}</a></body></html>"
+    )
+}
+
+/// Escape `s` for use in HTML text or attribute values.
+pub fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Tracks which source produced each output path, so that two sources never silently write
+/// to the same file, and which of them were actually written.
+#[derive(Debug, Default)]
+pub struct OutputPaths {
+    claimed: HashMap<PathBuf, String>,
+    written: HashSet<PathBuf>,
+}
+
+impl OutputPaths {
+    /// Record that `source` writes `dest`. `source` is only used in the error message, it is
+    /// usually the path of the source file.
+    pub fn claim(&mut self, dest: &Path, source: impl Display) -> Result<()> {
+        let source = source.to_string();
+        match self.claimed.get(dest) {
+            Some(existing) if *existing != source => Err(Error::OutputCollision {
+                dest: dest.to_path_buf(),
+                first: existing.clone(),
+                second: source,
+            }),
+            _ => {
+                self.claimed.insert(dest.to_path_buf(), source);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn contains(&self, dest: &Path) -> bool {
+        self.claimed.contains_key(dest)
+    }
+
+    /// Record that `dest` is in place in the output directory, whether it was written by this
+    /// build or kept from a previous one.
+    pub fn mark_written(&mut self, dest: &Path) {
+        self.written.insert(dest.to_path_buf());
+    }
+
+    pub fn is_written(&self, dest: &Path) -> bool {
+        self.written.contains(dest)
+    }
+
+    /// Fail if any of `pages` links to a URL whose output was not
+    /// [written](Self::mark_written). `listing` names the page containing the links, for the
+    /// error message.
+    pub fn check_links<'p>(
+        &self,
+        output_dir: &Path,
+        listing: &str,
+        pages: impl IntoIterator<Item = &'p ParsedFile<'p>>,
+    ) -> Result<()> {
+        for page in pages {
+            let dest = crate::url::output_path(output_dir, &page.meta.url);
+            if !self.is_written(&dest) {
+                return Err(Error::BrokenLink {
+                    listing: listing.to_string(),
+                    url: page.meta.url.clone(),
+                    path: page.meta.path.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The outputs claimed by `source`.
+    pub fn claimed_by<'s>(&'s self, source: &'s str) -> impl Iterator<Item = &'s Path> {
+        self.claimed
+            .iter()
+            .filter(move |(_, claimant)| *claimant == source)
+            .map(|(dest, _)| dest.as_path())
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.claimed.keys().map(PathBuf::as_path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use comrak::{Arena, ComrakOptions};
+
+    use super::*;
+    use crate::md::MarkdownParser;
+
+    #[test]
+    fn test_check_links() {
+        let arena = Arena::new();
+        let opts = ComrakOptions::default();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let page = parser.test_page("intro.md", "# Hi");
+        let out = Path::new("/out");
+        let dest = crate::url::output_path(out, &page.meta.url);
+
+        // Claimed, but rendering it failed.
+        let mut outputs = OutputPaths::default();
+        outputs.claim(&dest, "/src/intro.md").unwrap();
+        let err = outputs
+            .check_links(out, "the index page", [&page])
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::BrokenLink { url, .. } if url == "/intro/"),
+            "{err:?}"
+        );
+
+        outputs.mark_written(&dest);
+        outputs.check_links(out, "the index page", [&page]).unwrap();
+    }
+}
@@ -21,13 +21,9 @@
 //!     3 - html: generate HTML -> metadata at this stage contains relative paths to the files
 //!
 
-use comrak::ComrakOptions;
-use std::path::PathBuf;
-
 #[allow(unused_imports)]
 mod internal_prelude {
-    pub use color_eyre::eyre::{WrapErr, eyre};
-    pub use color_eyre::{Context, Result};
+    pub use crate::error::{Error, Result};
     pub use tracing::{debug, error, info, trace, warn};
 }
 
@@ -35,6 +31,16 @@ pub mod comrak {
     pub use comrak::*;
 }
 
+pub mod diagnostic;
+pub mod error;
+pub mod feed;
 pub mod fs;
 pub mod html;
 pub mod md;
+pub mod paginate;
+pub mod schema;
+pub mod sitemap;
+pub mod taxonomy;
+pub mod url;
+
+pub use error::Error;
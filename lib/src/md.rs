@@ -1,32 +1,40 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use comrak::{
-    Arena, ComrakOptions, ExtensionOptions, arena_tree::Node, nodes::Ast, parse_document,
+    Arena, ComrakOptions,
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+    parse_document,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize, de::DeserializeOwned};
 
 use crate::{
+    diagnostic::{Diagnostic, Diagnostics, Location},
     fs::{FileMeta, FileType},
     html::GeneratedFile,
     internal_prelude::*,
+    schema::Schemas,
 };
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
     ops::{Deref, DerefMut},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+/// File stem of the page in a page bundle.
+pub const BUNDLE_INDEX_STEM: &str = "index";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParsedFileMeta {
     pub path: PathBuf,
+    pub rel_path: PathBuf,
     pub date: DateTime<Utc>,
     pub file_type: FileType,
+    /// Site-relative URL of the page, filled in by [`crate::url::Permalinks::apply`].
+    pub url: String,
 }
 
 impl ParsedFileMeta {
-    pub fn file_name_no_stem(&self) -> &str {
-        self.path.file_name().and_then(|s| s.to_str()).unwrap_or("")
-    }
-
     pub fn stem(&self) -> &str {
         self.path.file_stem().and_then(|s| s.to_str()).unwrap_or("")
     }
@@ -34,6 +42,37 @@ impl ParsedFileMeta {
     pub fn file_name(&self) -> &str {
         self.path.file_name().and_then(|s| s.to_str()).unwrap_or("")
     }
+
+    /// Whether this is the `index.md` of a page bundle: a directory holding the page together
+    /// with its assets. The page takes the name of the directory.
+    ///
+    /// The `index.md` at the source root is a plain page: the source root is not a bundle.
+    pub fn is_bundle_index(&self) -> bool {
+        self.bundle_dir().is_some()
+    }
+
+    /// The directory of the bundle, relative to the source root, if this is a bundle index.
+    pub fn bundle_dir(&self) -> Option<&Path> {
+        if self.file_type != FileType::Markdown || self.stem() != BUNDLE_INDEX_STEM {
+            return None;
+        }
+        self.rel_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+    }
+
+    /// The top-level directory the file lives in, or `""` for files at the source root.
+    pub fn section(&self) -> &str {
+        section(&self.rel_path)
+    }
+}
+
+fn section(rel_path: &Path) -> &str {
+    let mut components = rel_path.components();
+    match (components.next(), components.next()) {
+        (Some(first), Some(_)) => first.as_os_str().to_str().unwrap_or(""),
+        _ => "",
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -47,34 +86,297 @@ impl<'a> ParsedFile<'a> {
         ParsedFile {
             meta: ParsedFileMeta {
                 path: meta.path.clone(),
+                rel_path: meta.rel_path.clone(),
                 date: meta.date,
                 file_type: meta.file_type,
+                url: String::new(),
             },
             contents,
         }
     }
 
-    fn to_html(&self, options: &ComrakOptions) -> Result<GeneratedFile> {
-        Ok(crate::html::generate_html(&self, options))
+    /// The frontmatter `title`, defaulting to the file (or bundle directory) name.
+    pub fn title(&self) -> &str {
+        let fallback = || {
+            self.meta
+                .bundle_dir()
+                .and_then(|dir| dir.file_name())
+                .and_then(|s| s.to_str())
+                .unwrap_or_else(|| self.meta.stem())
+        };
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.title.as_deref())
+            .unwrap_or_else(fallback)
+    }
+
+    /// The frontmatter `date`, defaulting to the file's modified date.
+    pub fn date(&self) -> DateTime<Utc> {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.date)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|nd| DateTime::from_naive_utc_and_offset(nd, Utc))
+            .unwrap_or(self.meta.date)
+    }
+
+    /// The frontmatter `updated`, defaulting to [`Self::date`].
+    pub fn updated(&self) -> DateTime<Utc> {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.updated)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|nd| DateTime::from_naive_utc_and_offset(nd, Utc))
+            .unwrap_or_else(|| self.date())
+    }
+
+    /// The frontmatter `aliases`: URLs that redirect to the page.
+    pub fn aliases(&self) -> &[String] {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.aliases.as_deref())
+            .unwrap_or(&[])
+    }
+
+    /// Whether the page belongs in the sitemap, which is the default.
+    pub fn in_sitemap(&self) -> bool {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.sitemap)
+            .unwrap_or(true)
+    }
+
+    pub fn is_draft(&self) -> bool {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.draft)
+            .unwrap_or(false)
+    }
+
+    /// Whether the page is not out yet on `today`, going by its `publish_date`, or its `date`
+    /// without one.
+    pub fn is_scheduled(&self, today: NaiveDate) -> bool {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.publish_date.or(fm.date))
+            .is_some_and(|publish| publish > today)
+    }
+
+    /// Whether the page's `expiry_date` has been reached on `today`.
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.expiry_date)
+            .is_some_and(|expiry| expiry <= today)
+    }
+
+    /// The frontmatter value `key` that has no field of its own in [`FrontmatterData`], as a
+    /// `T`. `None` if the key is not set.
+    pub fn extra<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let Some(value) = self
+            .contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.extra.get(key))
+        else {
+            return Ok(None);
+        };
+        let value = T::deserialize(value).map_err(|e| Error::InvalidValue {
+            path: self.meta.path.clone(),
+            key: key.to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(Some(value))
+    }
+
+    /// The page's terms in `taxonomy`: `tags`, the `taxonomy` list of the frontmatter
+    /// `taxonomies` table, or any other frontmatter key holding a string or a list of strings.
+    pub fn terms(&self, taxonomy: &str) -> Vec<&str> {
+        let Some(fm) = self.contents.frontmatter.as_ref() else {
+            return vec![];
+        };
+        if taxonomy == "tags" {
+            return fm.tags.iter().flatten().map(String::as_str).collect();
+        }
+        if let Some(terms) = fm.taxonomies.as_ref().and_then(|t| t.get(taxonomy)) {
+            return terms.iter().map(String::as_str).collect();
+        }
+        match fm.extra.get(taxonomy) {
+            Some(serde_json::Value::String(term)) => vec![term.as_str()],
+            Some(serde_json::Value::Array(terms)) => {
+                terms.iter().filter_map(serde_json::Value::as_str).collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// The frontmatter `summary`, falling back to `description`.
+    pub fn summary(&self) -> Option<&str> {
+        let fm = self.contents.frontmatter.as_ref()?;
+        fm.summary.as_deref().or(fm.description.as_deref())
+    }
+
+    /// The frontmatter `description`, falling back to `summary`.
+    pub fn description(&self) -> Option<&str> {
+        let fm = self.contents.frontmatter.as_ref()?;
+        fm.description.as_deref().or(fm.summary.as_deref())
+    }
+
+    /// The first paragraph of a markdown page, as plain text.
+    pub fn excerpt(&self) -> Option<String> {
+        let Body::Markdown(root) = &self.contents.body else {
+            return None;
+        };
+        let paragraph = root
+            .children()
+            .find(|node| matches!(node.data.borrow().value, NodeValue::Paragraph))?;
+        let mut text = String::new();
+        for node in paragraph.descendants() {
+            match &node.data.borrow().value {
+                NodeValue::Text(t) => text.push_str(t),
+                NodeValue::Code(code) => text.push_str(&code.literal),
+                NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+                _ => {}
+            }
+        }
+        Some(text)
+    }
+
+    pub fn to_html(&self, options: &ComrakOptions) -> Result<GeneratedFile> {
+        crate::html::generate_html(self, options)
     }
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+/// The frontmatter of a page.
+///
+/// Dates can also be written as datetimes, only their date is kept, see [`parse_date`].
+///
+/// Keys without a field of their own are kept in [`Self::extra`], see [`ParsedFile::extra`]
+/// to read them as a specific type.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct FrontmatterData {
     pub title: Option<String>,
+    pub description: Option<String>,
+    pub summary: Option<String>,
+    /// Not used by aaska itself, feeds name the site's author.
+    pub author: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_date")]
     pub date: Option<NaiveDate>,
+    /// When the page last changed in a way worth telling search engines about.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub updated: Option<NaiveDate>,
     pub tags: Option<Vec<String>>,
+    /// Not used by aaska itself, for templates that order pages by it.
+    pub weight: Option<i64>,
+    pub slug: Option<String>,
+    /// Former URLs of the page, each gets a page redirecting to it.
+    pub aliases: Option<Vec<String>>,
+    /// Site-relative URL of the page, without `.` or `..` segments, see
+    /// [`crate::url::Permalinks`].
+    #[serde(default, deserialize_with = "deserialize_permalink")]
+    pub permalink: Option<String>,
+    pub layout: Option<String>,
+    /// Set to `false` to leave the page out of the sitemap.
+    pub sitemap: Option<bool>,
+    pub draft: Option<bool>,
+    /// The page is left out of builds until this date, see [`ParsedFile::is_scheduled`].
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub publish_date: Option<NaiveDate>,
+    /// The page is left out of builds from this date on.
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub expiry_date: Option<NaiveDate>,
+    /// Terms of the taxonomies other than `tags`, by taxonomy name.
+    pub taxonomies: Option<BTreeMap<String, Vec<String>>>,
+    /// Every other key, such as the terms of custom taxonomies.
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Parse a `YYYY-MM-DD` date, or the date of a datetime such as `2019-03-26T08:47:11+01:00`
+/// or `2019-03-26 08:47:11`. A datetime with an offset is converted to UTC first, one without
+/// is taken as UTC already.
+pub fn parse_date(s: &str) -> Option<NaiveDate> {
+    const DATETIME_FORMATS: [&str; 4] = [
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M",
+    ];
+    let s = s.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(date);
+    }
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(&s.replacen(' ', "T", 1)) {
+        return Some(datetime.naive_utc().date());
+    }
+    // YAML also allows a space before the offset.
+    if let Ok(datetime) = DateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f %#z") {
+        return Some(datetime.naive_utc().date());
+    }
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+        .map(|datetime| datetime.date())
+}
+
+fn deserialize_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<NaiveDate>, D::Error> {
+    let Some(s) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    parse_date(&s).map(Some).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "invalid date {s:?}, expected YYYY-MM-DD or a datetime"
+        ))
+    })
+}
+
+fn deserialize_permalink<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    let permalink = Option::<String>::deserialize(deserializer)?;
+    match &permalink {
+        Some(url) if url.split('/').any(|segment| matches!(segment, "." | "..")) => {
+            Err(serde::de::Error::custom(format!(
+                "invalid permalink {url:?}, `.` and `..` segments are not allowed"
+            )))
+        }
+        _ => Ok(permalink),
+    }
 }
 
+/// Frontmatter keys without a field in [`FrontmatterData`].
+pub type Extra = BTreeMap<String, serde_json::Value>;
+
 #[derive(Debug, Clone)]
 pub struct FileContents<'a> {
     pub frontmatter: Option<FrontmatterData>,
-    pub body_ast: &'a Node<'a, RefCell<Ast>>,
+    pub body: Body<'a>,
+}
+
+/// The content of a file after its frontmatter, depending on its [`FileType`].
+#[derive(Debug, Clone)]
+pub enum Body<'a> {
+    Markdown(&'a Node<'a, RefCell<Ast>>),
+    /// Raw HTML, passed through as is.
+    Html(String),
+    /// Text rendered as a preformatted block.
+    PlainText(String),
 }
 
 pub struct MarkdownParser<'a, 'c> {
     arena: &'a Arena<Node<'a, RefCell<Ast>>>,
     options: &'c ComrakOptions<'c>,
+    schemas: Schemas,
 }
 
 impl<'a, 'c> MarkdownParser<'a, 'c> {
@@ -82,32 +384,156 @@ impl<'a, 'c> MarkdownParser<'a, 'c> {
         arena: &'a Arena<Node<'a, RefCell<Ast>>>,
         options: &'c ComrakOptions,
     ) -> Self {
-        MarkdownParser { arena, options }
+        MarkdownParser {
+            arena,
+            options,
+            schemas: Schemas::default(),
+        }
     }
 
-    pub fn parse_markdown(&self, content: &str) -> Result<FileContents<'a>> {
-        // First, try to extract frontmatter if it exists
-        let (frontmatter, body_content) = extract_frontmatter(content)?;
-
-        let root: &'a Node<'a, RefCell<Ast>> =
-            parse_document(self.arena, &body_content, &self.options);
+    /// Check the frontmatter of every page in [`Self::parse_many`] against `schemas`.
+    pub fn with_schemas(mut self, schemas: Schemas) -> Self {
+        self.schemas = schemas;
+        self
+    }
 
+    /// Parse `content` as markdown.
+    ///
+    /// The [`Diagnostics`] of frontmatter that does not parse have no
+    /// [path](Diagnostic::path), since `content` may not come from a file: they render as
+    /// `<input>`. [`Self::parse_many`] names the file of each problem.
+    pub fn parse_markdown(&self, content: &str) -> Result<FileContents<'a>> {
+        let (frontmatter, body) = extract_frontmatter(content)?;
         Ok(FileContents {
             frontmatter,
-            body_ast: root,
+            body: self
+                .body(body, FileType::Markdown)
+                .expect("Markdown is content"),
         })
     }
 
+    /// Parse `content` according to `file_type`. Every type supports frontmatter.
+    ///
+    /// Like [`Self::parse_markdown`], diagnostics have no path. Returns `None` for
+    /// [`FileType::Unsupported`], which is not content but an asset.
+    pub fn parse(&self, content: &str, file_type: FileType) -> Result<Option<FileContents<'a>>> {
+        if file_type == FileType::Unsupported {
+            return Ok(None);
+        }
+        let (frontmatter, body) = extract_frontmatter(content)?;
+        Ok(self
+            .body(body, file_type)
+            .map(|body| FileContents { frontmatter, body }))
+    }
+
+    /// Parse every content file in `files`, skipping assets.
+    ///
+    /// Frontmatter that does not parse or breaks the [schema](Self::with_schemas) of its
+    /// section fails the whole call, but only after every file was checked, with a
+    /// [`Diagnostics`] error listing every problem.
     pub fn parse_many(&self, files: &'a Vec<FileMeta>) -> Result<Vec<ParsedFile<'a>>> {
+        let (parsed, errors) = self.parse_many_keep_going(files);
+        let mut diagnostics = vec![];
+        for error in errors {
+            match error {
+                Error::Parse(problems) => diagnostics.extend(problems.0),
+                error => return Err(error),
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            return Err(Diagnostics(diagnostics).into());
+        }
+        Ok(parsed)
+    }
+
+    /// Like [`Self::parse_many`], but a file that fails is skipped rather than failing the
+    /// call. Returns the files that parsed, and one error for each file that did not.
+    pub fn parse_many_keep_going(
+        &self,
+        files: &'a Vec<FileMeta>,
+    ) -> (Vec<ParsedFile<'a>>, Vec<Error>) {
         let mut acc = vec![];
+        let mut errors = vec![];
         for f in files {
-            let content = crate::fs::read_file(&f.path)?;
-            let parsed_content = self.parse_markdown(&content)?;
+            if f.file_type == FileType::Unsupported {
+                continue;
+            }
 
-            acc.push(ParsedFile::new(f, parsed_content));
+            let content = match crate::fs::read_file(&f.path) {
+                Ok(content) => content,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let (raw, body) = split_frontmatter(&content);
+            match self.check_frontmatter(raw.as_ref(), section(&f.rel_path)) {
+                Ok(frontmatter) => {
+                    if let Some(body) = self.body(body, f.file_type) {
+                        acc.push(ParsedFile::new(f, FileContents { frontmatter, body }));
+                    }
+                }
+                Err(problems) => {
+                    let problems = problems
+                        .into_iter()
+                        .map(|problem| problem.in_file(&f.path).with_source(&content));
+                    errors.push(Diagnostics(problems.collect()).into());
+                }
+            }
         }
+        (acc, errors)
+    }
+
+    /// Parse and validate frontmatter, collecting every problem.
+    fn check_frontmatter(
+        &self,
+        raw: Option<&RawFrontmatter>,
+        section: &str,
+    ) -> std::result::Result<Option<FrontmatterData>, Vec<Diagnostic>> {
+        let value = raw
+            .map(RawFrontmatter::value)
+            .transpose()
+            .map_err(|e| vec![e])?;
 
-        Ok(acc)
+        let mut problems = match self.schemas.for_section(section) {
+            Some(schema) => schema.check(
+                value.as_ref(),
+                Location::line(raw.map_or(1, |raw| raw.line)),
+                |key| raw?.locate(key),
+            ),
+            None => vec![],
+        };
+        let frontmatter = match (raw, value) {
+            (Some(raw), Some(value)) => match raw.deserialize(value) {
+                Ok(frontmatter) => Some(frontmatter),
+                Err(mut errors) => {
+                    problems.append(&mut errors);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        if problems.is_empty() {
+            Ok(frontmatter)
+        } else {
+            problems.sort_by_key(|problem| problem.location);
+            Err(problems)
+        }
+    }
+
+    fn body(&self, body: String, file_type: FileType) -> Option<Body<'a>> {
+        match file_type {
+            FileType::Markdown => Some(Body::Markdown(parse_document(
+                self.arena,
+                &body,
+                self.options,
+            ))),
+            FileType::HTML => Some(Body::Html(body)),
+            FileType::PlainText => Some(Body::PlainText(body)),
+            FileType::Unsupported => None,
+        }
     }
 }
 
@@ -123,35 +549,13 @@ impl<'a> From<Vec<ParsedFile<'a>>> for PageList<'a> {
 }
 
 impl<'c> PageList<'c> {
-    pub fn iter(&'c self) -> impl Iterator<Item = &'c ParsedFile<'c>> {
+    pub fn iter(&self) -> impl Iterator<Item = &ParsedFile<'c>> {
         self.files.iter()
     }
 
-    pub fn sorted_by_date(&'c self) -> Vec<&'c ParsedFile<'c>> {
+    pub fn sorted_by_date(&self) -> Vec<&ParsedFile<'c>> {
         let mut sorted_files: Vec<&ParsedFile> = self.files.iter().collect();
-        sorted_files.sort_by(|a, b| {
-            // First try to use the frontmatter date, if it exists
-            let date_a = a.contents.frontmatter.as_ref().and_then(|fm| fm.date);
-            let date_b = b.contents.frontmatter.as_ref().and_then(|fm| fm.date);
-
-            // default to the file's date if frontmatter date is not available
-            let date_a = date_a
-                .and_then(|d| {
-                    d.and_hms_opt(0, 0, 0)
-                        .map(|nd| DateTime::from_naive_utc_and_offset(nd, Utc))
-                })
-                .unwrap_or(a.meta.date);
-
-            let date_b = date_b
-                .and_then(|d| {
-                    d.and_hms_opt(0, 0, 0)
-                        .map(|nd| DateTime::from_naive_utc_and_offset(nd, Utc))
-                })
-                .unwrap_or(b.meta.date);
-
-            date_a.cmp(&date_b)
-        });
-
+        sorted_files.sort_by_key(|file| file.date());
         sorted_files
     }
 }
@@ -173,54 +577,283 @@ impl DerefMut for Html {
     }
 }
 
-fn extract_frontmatter(content: &str) -> Result<(Option<FrontmatterData>, String)> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrontmatterFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+/// Frontmatter as written in the file, before it is parsed.
+#[derive(Debug)]
+struct RawFrontmatter {
+    format: FrontmatterFormat,
+    text: String,
+    /// 1-based line of the file `text` starts on.
+    line: usize,
+}
+
+impl RawFrontmatter {
+    /// Parse into a generic value, or where the syntax error is.
+    fn value(&self) -> std::result::Result<serde_json::Value, Diagnostic> {
+        let value = match self.format {
+            FrontmatterFormat::Yaml => serde_yaml::from_str(&self.text).map_err(|e| {
+                let location = e.location().map_or(Location::line(self.line), |l| {
+                    Location::new(self.line + l.line() - 1, l.column())
+                });
+                Diagnostic::new(
+                    location,
+                    format!("Failed to parse frontmatter as YAML: {e}"),
+                )
+            })?,
+            FrontmatterFormat::Toml => toml::from_str(&self.text)
+                .map(|table| toml_to_json(toml::Value::Table(table)))
+                .map_err(|e| {
+                    let location = match e.span() {
+                        Some(span) => self.offset_location(span.start),
+                        None => Location::line(self.line),
+                    };
+                    // toml spreads its message over several lines, and leaves it empty when
+                    // a value is missing.
+                    let message = e.message().trim().replace('\n', ", ");
+                    let message = if message.is_empty() {
+                        "expected a value"
+                    } else {
+                        &message
+                    };
+                    Diagnostic::new(
+                        location,
+                        format!("Failed to parse frontmatter as TOML: {message}"),
+                    )
+                })?,
+            FrontmatterFormat::Json => serde_json::from_str(&self.text).map_err(|e| {
+                // serde_json counts bytes, the snippet counts characters.
+                let line = e.line().saturating_sub(1);
+                let column = match self.text.lines().nth(line) {
+                    Some(text) => char_column(text, e.column().saturating_sub(1)),
+                    None => e.column(),
+                };
+                Diagnostic::new(
+                    Location::new(self.line + line, column),
+                    format!("Failed to parse frontmatter as JSON: {e}"),
+                )
+            })?,
+        };
+        // An empty block is no keys at all.
+        Ok(match value {
+            serde_json::Value::Null => serde_json::Value::Object(Default::default()),
+            value => value,
+        })
+    }
+
+    /// Deserialize `value`, blaming the offending keys when that fails.
+    fn deserialize(
+        &self,
+        value: serde_json::Value,
+    ) -> std::result::Result<FrontmatterData, Vec<Diagnostic>> {
+        let error = match FrontmatterData::deserialize(&value) {
+            Ok(frontmatter) => return Ok(frontmatter),
+            Err(e) => e,
+        };
+        let start = Location::line(self.line);
+        let serde_json::Value::Object(map) = &value else {
+            return Err(vec![Diagnostic::new(
+                start,
+                format!("Frontmatter must be a table of keys, found {value}"),
+            )]);
+        };
+
+        let problems: Vec<_> = map
+            .iter()
+            .filter_map(|(key, value)| {
+                let single =
+                    serde_json::Value::Object([(key.clone(), value.clone())].into_iter().collect());
+                let e = FrontmatterData::deserialize(&single).err()?;
+                Some(Diagnostic::new(
+                    self.locate(key).unwrap_or(start),
+                    format!("Invalid `{key}`: {e}"),
+                ))
+            })
+            .collect();
+        if problems.is_empty() {
+            Err(vec![Diagnostic::new(
+                start,
+                format!("Invalid frontmatter: {error}"),
+            )])
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Where the value of a top-level `key` starts.
+    fn locate(&self, key: &str) -> Option<Location> {
+        let quoted = format!("\"{key}\"");
+        // The delimiter of the TOML multi-line string the line is in, if any.
+        let mut open_string: Option<&str> = None;
+        self.text.lines().enumerate().find_map(|(i, line)| {
+            if self.format == FrontmatterFormat::Toml {
+                let odd = |delimiter: &str| line.matches(delimiter).count() % 2 == 1;
+                match open_string {
+                    Some(delimiter) => {
+                        if odd(delimiter) {
+                            open_string = None;
+                        }
+                        return None;
+                    }
+                    None => open_string = ["\"\"\"", "'''"].into_iter().find(|d| odd(d)),
+                }
+            }
+            let rest = match self.format {
+                // Nested keys are indented in YAML and come after a `[table]` in TOML, the
+                // first match is the top-level one either way.
+                FrontmatterFormat::Yaml | FrontmatterFormat::Toml => line.strip_prefix(key),
+                FrontmatterFormat::Json => line.trim_start().strip_prefix(quoted.as_str()),
+            }?;
+            let value = rest.trim_start().strip_prefix([':', '='])?.trim_start();
+            Some(Location::new(
+                self.line + i,
+                char_column(line, line.len() - value.len()),
+            ))
+        })
+    }
+
+    /// The location of byte `offset` into the frontmatter.
+    fn offset_location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.text.len());
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        Location::new(
+            self.line + self.text[..offset].matches('\n').count(),
+            char_column(&self.text[line_start..], offset - line_start),
+        )
+    }
+}
+
+/// Split `content` into its frontmatter and body. The frontmatter is either YAML between `---`
+/// lines, TOML between `+++` lines, or a JSON object at the very start.
+fn split_frontmatter(content: &str) -> (Option<RawFrontmatter>, String) {
+    // Lines are counted from the untrimmed file.
+    let leading_lines = content[..content.len() - content.trim_start().len()]
+        .matches('\n')
+        .count();
     let content = content.trim();
 
-    // Check if content starts with frontmatter delimiter
-    if !content.starts_with("---") {
-        return Ok((None, content.to_string()));
+    if content.starts_with('{') {
+        let mut values =
+            serde_json::Deserializer::from_str(content).into_iter::<serde::de::IgnoredAny>();
+        if let Some(Ok(_)) = values.next() {
+            let end = values.byte_offset();
+            let raw = RawFrontmatter {
+                format: FrontmatterFormat::Json,
+                text: content[..end].to_string(),
+                line: leading_lines + 1,
+            };
+            return (Some(raw), content[end..].trim_start().to_string());
+        }
+        // Not JSON, so no frontmatter.
+        return (None, content.to_string());
     }
 
+    let (delimiter, format) = if content.starts_with("---") {
+        ("---", FrontmatterFormat::Yaml)
+    } else if content.starts_with("+++") {
+        ("+++", FrontmatterFormat::Toml)
+    } else {
+        return (None, content.to_string());
+    };
+
     // Find the closing delimiter
     let lines: Vec<&str> = content.lines().collect();
     if lines.len() < 3 {
-        return Ok((None, content.to_string()));
+        return (None, content.to_string());
     }
 
-    // Look for the closing --- after the first line
-    let mut end_index = None;
-    for (i, line) in lines.iter().enumerate().skip(1) {
-        if line.trim() == "---" {
-            end_index = Some(i);
-            break;
-        }
-    }
+    // Look for the closing delimiter after the first line
+    let end_index = lines
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, line)| line.trim() == delimiter)
+        .map(|(i, _)| i);
 
     match end_index {
         Some(end) => {
-            // Extract frontmatter content (excluding the --- delimiters)
-            let frontmatter_lines = &lines[1..end];
-            let frontmatter_content = frontmatter_lines.join("\n");
-
-            let frontmatter: FrontmatterData = serde_yaml::from_str(&frontmatter_content)
-                .map_err(|e| eyre!("Failed to parse frontmatter as YAML: {}", e))?;
-
-            // Get the body content (everything after the closing ---)
-            let body_lines = &lines[end + 1..];
-            let body_content = body_lines.join("\n");
-
-            Ok((Some(frontmatter), body_content))
+            // Frontmatter content (excluding the delimiters), and everything after it
+            let raw = RawFrontmatter {
+                format,
+                text: lines[1..end].join("\n"),
+                line: leading_lines + 2,
+            };
+            (Some(raw), lines[end + 1..].join("\n"))
         }
         None => {
             // No closing delimiter found, treat entire content as body
-            Ok((None, content.to_string()))
+            (None, content.to_string())
+        }
+    }
+}
+
+/// [`split_frontmatter`] and parse the frontmatter, failing on the first problem.
+fn extract_frontmatter(content: &str) -> Result<(Option<FrontmatterData>, String)> {
+    let (raw, body) = split_frontmatter(content);
+    let Some(raw) = raw else {
+        return Ok((None, body));
+    };
+    let value = raw.value().map_err(|e| e.with_source(content))?;
+    let frontmatter = raw.deserialize(value).map_err(|problems| {
+        Diagnostics(
+            problems
+                .into_iter()
+                .map(|problem| problem.with_source(content))
+                .collect(),
+        )
+    })?;
+    Ok((Some(frontmatter), body))
+}
+
+/// The 1-based column of byte `offset` into `line`, counted in characters like the carets
+/// of [`Diagnostic`] snippets.
+fn char_column(line: &str, offset: usize) -> usize {
+    line.char_indices().take_while(|(i, _)| *i < offset).count() + 1
+}
+
+/// TOML has its own date and time type, which the other formats write as strings.
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => serde_json::Value::String(s),
+        toml::Value::Integer(i) => i.into(),
+        toml::Value::Float(f) => f.into(),
+        toml::Value::Boolean(b) => b.into(),
+        toml::Value::Datetime(dt) => serde_json::Value::String(dt.to_string()),
+        toml::Value::Array(array) => array.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, toml_to_json(value)))
+            .collect(),
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl<'a> MarkdownParser<'a, '_> {
+    /// A page parsed from `markdown` as if read from `/src/<rel_path>`, last modified on
+    /// 2024-01-02, at the URL of its path without the extension. For tests.
+    pub fn test_page(&self, rel_path: &str, markdown: &str) -> ParsedFile<'a> {
+        let rel_path = PathBuf::from(rel_path);
+        ParsedFile {
+            meta: ParsedFileMeta {
+                path: Path::new("/src").join(&rel_path),
+                url: format!("/{}/", rel_path.with_extension("").display()),
+                rel_path,
+                date: "2024-01-02T00:00:00Z".parse().unwrap(),
+                file_type: FileType::Markdown,
+            },
+            contents: self.parse_markdown(markdown).unwrap(),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use std::cell::LazyCell;
+    use comrak::ExtensionOptions;
 
     use super::*;
 
@@ -267,6 +900,300 @@ Some body content here.
         );
     }
 
+    #[test]
+    fn test_terms() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let page = |markdown| parser.test_page("post.md", markdown);
+
+        let tabled = page("---\ntaxonomies:\n  series: [Intro]\n---\n");
+        assert_eq!(tabled.terms("series"), ["Intro"]);
+
+        let page = page("---\ntags: [a, b]\nseries: Intro\ncategories: [x]\n---\n");
+        assert_eq!(page.terms("tags"), ["a", "b"]);
+        assert_eq!(page.terms("series"), ["Intro"]);
+        assert_eq!(page.terms("categories"), ["x"]);
+        assert!(page.terms("authors").is_empty());
+    }
+
+    #[test]
+    fn test_extra() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let page = parser.test_page(
+            "post.md",
+            "---\nauthor: me\nweight: 3\nrating: 4\ncover: { src: a.png }\n---\n",
+        );
+
+        let fm = page.contents.frontmatter.as_ref().unwrap();
+        assert_eq!(fm.author.as_deref(), Some("me"));
+        assert_eq!(fm.weight, Some(3));
+        assert!(!fm.extra.contains_key("author"));
+
+        assert_eq!(page.extra::<u32>("rating").unwrap(), Some(4));
+        assert_eq!(page.extra::<u32>("missing").unwrap(), None);
+        assert!(page.extra::<String>("rating").is_err());
+
+        #[derive(Deserialize)]
+        struct Cover {
+            src: String,
+        }
+        let cover: Cover = page.extra("cover").unwrap().unwrap();
+        assert_eq!(cover.src, "a.png");
+    }
+
+    #[test]
+    fn test_publication() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let page = |markdown| parser.test_page("post.md", markdown);
+        let today = "2025-07-29".parse().unwrap();
+
+        assert!(page("---\ndraft: true\n---\n").is_draft());
+        assert!(!page("# Hi").is_draft());
+
+        assert!(page("---\npublish_date: 2025-07-30\n---\n").is_scheduled(today));
+        assert!(page("---\ndate: 2025-08-01\n---\n").is_scheduled(today));
+        assert!(
+            !page("---\ndate: 2025-08-01\npublish_date: 2025-07-29\n---\n").is_scheduled(today)
+        );
+
+        assert!(page("---\nexpiry_date: 2025-07-29\n---\n").is_expired(today));
+        assert!(!page("---\nexpiry_date: 2025-07-30\n---\n").is_expired(today));
+    }
+
+    #[test]
+    fn test_parse_toml_and_json_frontmatter() {
+        let expected = |fm: Option<FrontmatterData>| {
+            let fm = fm.expect("Should have frontmatter");
+            assert_eq!(fm.title, Some("Hello World".to_string()));
+            assert_eq!(fm.date, Some("2025-07-29".parse().unwrap()));
+            assert_eq!(fm.tags, Some(vec!["rust".to_string()]));
+            assert_eq!(fm.extra["series"], "Intro");
+        };
+
+        let (fm, body) = extract_frontmatter(
+            "+++\ntitle = \"Hello World\"\ndate = 2025-07-29\ntags = [\"rust\"]\nseries = \"Intro\"\n+++\n# Body",
+        )
+        .unwrap();
+        expected(fm);
+        assert_eq!(body, "# Body");
+
+        let (fm, body) = extract_frontmatter(
+            "{\n  \"title\": \"Hello World\",\n  \"date\": \"2025-07-29\",\n  \"tags\": [\"rust\"],\n  \"series\": \"Intro\"\n}\n# Body",
+        )
+        .unwrap();
+        expected(fm);
+        assert_eq!(body, "# Body");
+
+        let (fm, body) = extract_frontmatter("{not json} text").unwrap();
+        assert!(fm.is_none());
+        assert_eq!(body, "{not json} text");
+    }
+
+    #[test]
+    fn test_datetimes() {
+        let date = Some("2019-03-26".parse().unwrap());
+        for text in [
+            "+++\ndate = 2019-03-26T08:47:11+01:00\nupdated = 2019-03-26T08:47:11\n\
+             publish_date = 2019-03-26\nexpiry_date = 2019-03-26T18:30:00-05:00\n+++\n",
+            "---\ndate: 2019-03-26 08:47:11\nupdated: 2019-03-26T08:47:11Z\n\
+             publish_date: 2019-03-26 08:47:11.25 +01:00\nexpiry_date: \"2019-03-26\"\n---\n",
+            "{\"date\": \"2019-03-26T08:47:11+01:00\", \"updated\": \"2019-03-26 08:47\",\n\
+             \"publish_date\": \"2019-03-26T08:47:11.5\", \"expiry_date\": \"2019-03-26\"}\n",
+        ] {
+            let (fm, _) = extract_frontmatter(text).unwrap();
+            let fm = fm.unwrap();
+            assert_eq!(
+                [fm.date, fm.updated, fm.publish_date, fm.expiry_date],
+                [date; 4],
+                "{text}"
+            );
+        }
+
+        assert_eq!(parse_date("2019-03-26T08:47"), date);
+        assert_eq!(parse_date("2019-03-26 18:59:43.10 -05"), date);
+
+        // The day in UTC, not the one written.
+        let next = Some("2019-03-27".parse().unwrap());
+        assert_eq!(parse_date("2019-03-26T23:30:00-05:00"), next);
+        assert_eq!(parse_date("2019-03-26 21:59:43.10 -05"), next);
+        assert_eq!(parse_date("2019-03-27T00:30:00+01:00"), date);
+        assert_eq!(parse_date("2019-03-26 soon"), None);
+        assert_eq!(parse_date("2019-02-30"), None);
+    }
+
+    #[test]
+    fn test_permalink() {
+        let (fm, _) = extract_frontmatter("---\npermalink: /about.html\n---\n").unwrap();
+        assert_eq!(fm.unwrap().permalink.as_deref(), Some("/about.html"));
+
+        for text in [
+            "---\npermalink: ../x\n---\n",
+            "+++\npermalink = \"/a/./b/\"\n+++\n",
+        ] {
+            let err = extract_frontmatter(text).unwrap_err();
+            assert!(err.to_string().contains("invalid permalink"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_check_frontmatter() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let schema: crate::schema::Schema =
+            toml::from_str("required = [\"title\"]\ntypes = { weight = \"integer\" }").unwrap();
+        let parser = MarkdownParser::with_arena(&arena, &opts).with_schemas(Schemas {
+            default: None,
+            sections: [("posts".to_string(), schema)].into(),
+        });
+        let check = |content: &str, section| {
+            let (raw, _) = split_frontmatter(content);
+            parser.check_frontmatter(raw.as_ref(), section)
+        };
+
+        let content = "\n---\ntitle: Hi\ndate: 2025-13-01\nweight: heavy\n---\n";
+        let problems = check(content, "posts").unwrap_err();
+        let locations: Vec<_> = problems.iter().map(|p| p.location).collect();
+        assert_eq!(
+            locations,
+            [
+                Location::new(4, 7),
+                Location::new(5, 9),
+                Location::new(5, 9)
+            ]
+        );
+        assert!(problems[0].message.starts_with("Invalid `date`"));
+
+        let problems = check("# No frontmatter", "posts").unwrap_err();
+        assert_eq!(problems[0].location, Location::line(1));
+        assert!(check("# No frontmatter", "pages").unwrap().is_none());
+
+        let problems = check("+++\ntitle = \"Hi\"\nweight = \n+++\n", "posts").unwrap_err();
+        assert_eq!(problems[0].location.line, 3);
+        assert!(
+            problems[0]
+                .message
+                .starts_with("Failed to parse frontmatter as TOML")
+        );
+
+        let problems = check("---\ntitle: Hi\ntags: [a\n---\n", "pages").unwrap_err();
+        assert_eq!(problems[0].location.line, 4);
+        assert!(problems[0].location.column.is_some());
+    }
+
+    #[test]
+    fn test_locate() {
+        let raw = |format, text: &str| RawFrontmatter {
+            format,
+            text: text.to_string(),
+            line: 2,
+        };
+
+        // Lines inside multi-line strings are not keys, even when they look like one.
+        let toml = raw(
+            FrontmatterFormat::Toml,
+            "description = \"\"\"\ntags = [\"fake\"]\n\"\"\"\nnote = '''\ntags = 1'''\ntags = [\"rust\"]\n",
+        );
+        assert_eq!(toml.locate("tags"), Some(Location::new(7, 8)));
+        assert_eq!(toml.locate("note"), Some(Location::new(5, 8)));
+
+        let yaml = raw(
+            FrontmatterFormat::Yaml,
+            "description: |\n  tags: fake\ntags: [rust]\n",
+        );
+        assert_eq!(yaml.locate("tags"), Some(Location::new(4, 7)));
+
+        let json = raw(
+            FrontmatterFormat::Json,
+            "{\n  \"description\": \"tags: fake\",\n  \"tags\": [\"rust\"]\n}",
+        );
+        assert_eq!(json.locate("tags"), Some(Location::new(4, 11)));
+
+        // Columns count characters, not bytes.
+        let yaml = raw(FrontmatterFormat::Yaml, "åska: [1]\n");
+        assert_eq!(yaml.locate("åska"), Some(Location::new(2, 7)));
+        let toml = raw(FrontmatterFormat::Toml, "title = \"Åska\"\ndate = ö\n");
+        let error = toml.value().unwrap_err();
+        assert_eq!(error.location, Location::new(3, 8), "{error}");
+        let json = raw(FrontmatterFormat::Json, "{\"title\": \"Åska\", ö}");
+        let error = json.value().unwrap_err();
+        assert_eq!(error.location, Location::new(2, 19), "{error}");
+    }
+
+    #[test]
+    fn test_frontmatter_diagnostics() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let err = parser
+            .parse_markdown("---\ntitle: Hi\ndate: soon\n---\n# Body")
+            .unwrap_err();
+        let Error::Parse(diagnostics) = err else {
+            panic!("Expected a parse error, got {err:?}");
+        };
+        assert_eq!(diagnostics.0[0].location, Location::new(3, 7));
+        assert_eq!(diagnostics.0[0].source_line.as_deref(), Some("date: soon"));
+    }
+
+    #[test]
+    fn test_parse_many_keep_going() {
+        let dir = std::env::temp_dir().join(format!("aaska-keep-going-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("good.md"), "---\ntitle: Good\n---\n# Good").unwrap();
+        std::fs::write(
+            dir.join("bad.md"),
+            "---\ntitle: Bad\ndate: soon\n---\n# Bad",
+        )
+        .unwrap();
+        let files = crate::fs::list_files_dir_rec(&dir).unwrap();
+
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        assert!(matches!(parser.parse_many(&files), Err(Error::Parse(_))));
+
+        let (parsed, errors) = parser.parse_many_keep_going(&files);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].title(), "Good");
+        assert_eq!(errors.len(), 1);
+        let Error::Parse(diagnostics) = &errors[0] else {
+            panic!("Expected a parse error, got {:?}", errors[0]);
+        };
+        assert!(diagnostics.0[0].path.as_ref().unwrap().ends_with("bad.md"));
+    }
+
+    #[test]
+    fn test_parse_by_file_type() {
+        let arena = Arena::new();
+        let opts = default_opts();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+
+        let html = parser
+            .parse("---\ntitle: Raw\n---\n<p>raw</p>", FileType::HTML)
+            .unwrap()
+            .unwrap();
+        assert_eq!(html.frontmatter.unwrap().title, Some("Raw".to_string()));
+        assert!(matches!(html.body, Body::Html(body) if body == "<p>raw</p>"));
+
+        let text = parser
+            .parse("plain *text*", FileType::PlainText)
+            .unwrap()
+            .unwrap();
+        assert!(matches!(text.body, Body::PlainText(body) if body == "plain *text*"));
+
+        assert!(
+            parser
+                .parse("\u{89}PNG", FileType::Unsupported)
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_parse_no_frontmatter() {
         let markdown = r#"# Just a heading
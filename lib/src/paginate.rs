@@ -0,0 +1,82 @@
+//! Splitting long listings over several pages.
+//!
+//! The first page keeps the listing's own URL, the others get `page/<n>/` appended, so the
+//! second page of `/tags/rust/` is `/tags/rust/page/2/`.
+
+/// One page of a listing.
+#[derive(Debug)]
+pub struct Pager<'a, T> {
+    /// 1-based.
+    pub number: usize,
+    pub total: usize,
+    pub items: &'a [T],
+    /// Site-relative URL of this page.
+    pub url: String,
+    pub first: String,
+    pub last: String,
+    pub prev: Option<String>,
+    pub next: Option<String>,
+}
+
+/// Split `items` into pages of `size` items listed at `base_url`. A `size` of `None` (or 0)
+/// puts everything on one page. There is always at least one page, even without items.
+pub fn paginate<'a, T>(base_url: &str, items: &'a [T], size: Option<usize>) -> Vec<Pager<'a, T>> {
+    let chunks: Vec<&[T]> = match size {
+        Some(size) if size > 0 && !items.is_empty() => items.chunks(size).collect(),
+        _ => vec![items],
+    };
+    let total = chunks.len();
+    let url = |number: usize| page_url(base_url, number);
+
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, items)| {
+            let number = i + 1;
+            Pager {
+                number,
+                total,
+                items,
+                url: url(number),
+                first: url(1),
+                last: url(total),
+                prev: (number > 1).then(|| url(number - 1)),
+                next: (number < total).then(|| url(number + 1)),
+            }
+        })
+        .collect()
+}
+
+/// URL of page `number` of the listing at `base_url`.
+pub fn page_url(base_url: &str, number: usize) -> String {
+    if number <= 1 {
+        base_url.to_string()
+    } else {
+        format!("{}/page/{number}/", base_url.trim_end_matches('/'))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        let items = [1, 2, 3, 4, 5];
+        let pagers = paginate("/tags/rust/", &items, Some(2));
+        assert_eq!(pagers.len(), 3);
+        assert_eq!(pagers[0].url, "/tags/rust/");
+        assert_eq!(pagers[0].prev, None);
+        assert_eq!(pagers[0].next.as_deref(), Some("/tags/rust/page/2/"));
+        assert_eq!(pagers[1].items, [3, 4]);
+        assert_eq!(pagers[1].prev.as_deref(), Some("/tags/rust/"));
+        assert_eq!(pagers[2].items, [5]);
+        assert_eq!(pagers[2].last, "/tags/rust/page/3/");
+        assert_eq!(pagers[2].next, None);
+
+        assert_eq!(paginate("/", &items, None).len(), 1);
+        assert_eq!(paginate("/", &items, Some(0))[0].items.len(), 5);
+        assert_eq!(paginate::<i32>("/", &[], Some(2)).len(), 1);
+        assert_eq!(page_url("/", 2), "/page/2/");
+    }
+}
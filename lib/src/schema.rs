@@ -0,0 +1,252 @@
+//! Frontmatter schemas: per-section rules that every page's frontmatter must follow.
+//!
+//! Checks run in [`crate::md::MarkdownParser::parse_many`], which reports the violations of
+//! every page at once as [`crate::diagnostic::Diagnostics`] instead of stopping at the first.
+
+use std::{collections::BTreeMap, collections::HashMap, fmt};
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::{
+    diagnostic::{Diagnostic, Location},
+    md::parse_date,
+};
+
+/// The schema for each section, like [`crate::url::Permalinks`]: a section's own schema
+/// replaces the default one.
+#[derive(Debug, Clone, Default)]
+pub struct Schemas {
+    pub default: Option<Schema>,
+    pub sections: HashMap<String, Schema>,
+}
+
+impl Schemas {
+    pub fn for_section(&self, section: &str) -> Option<&Schema> {
+        self.sections.get(section).or(self.default.as_ref())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Schema {
+    /// Keys every page must set.
+    pub required: Vec<String>,
+    /// The type of each key, where set.
+    pub types: BTreeMap<String, FieldType>,
+    /// The values a key may take. For lists, such as `tags`, this applies to each item.
+    pub allowed: BTreeMap<String, Vec<String>>,
+    /// Earliest allowed `date`.
+    #[serde(deserialize_with = "deserialize_date")]
+    pub min_date: Option<NaiveDate>,
+    /// Latest allowed `date`.
+    #[serde(deserialize_with = "deserialize_date")]
+    pub max_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Integer,
+    Float,
+    Bool,
+    /// A `YYYY-MM-DD` date, or a datetime, see [`parse_date`].
+    Date,
+    List,
+    Table,
+}
+
+impl FieldType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            FieldType::String => value.is_string(),
+            FieldType::Integer => value.is_i64() || value.is_u64(),
+            FieldType::Float => value.is_number(),
+            FieldType::Bool => value.is_boolean(),
+            FieldType::Date => value.as_str().is_some_and(|s| parse_date(s).is_some()),
+            FieldType::List => value.is_array(),
+            FieldType::Table => value.is_object(),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::String => "a string",
+            FieldType::Integer => "an integer",
+            FieldType::Float => "a number",
+            FieldType::Bool => "a boolean",
+            FieldType::Date => "a YYYY-MM-DD date",
+            FieldType::List => "a list",
+            FieldType::Table => "a table",
+        };
+        f.write_str(name)
+    }
+}
+
+impl Schema {
+    /// Check `frontmatter`, an object or `None` for pages without frontmatter. `locate` finds
+    /// the value of a key in the file; anything it cannot place is reported at `start`.
+    pub fn check(
+        &self,
+        frontmatter: Option<&Value>,
+        start: Location,
+        locate: impl Fn(&str) -> Option<Location>,
+    ) -> Vec<Diagnostic> {
+        let mut problems = Vec::new();
+        let get = |key: &str| frontmatter.and_then(|fm| fm.get(key));
+        let at = |key: &str| locate(key).unwrap_or(start);
+
+        for key in &self.required {
+            if get(key).is_none_or(Value::is_null) {
+                problems.push(Diagnostic::new(
+                    start,
+                    format!("missing required key `{key}`"),
+                ));
+            }
+        }
+
+        for (key, ty) in &self.types {
+            if let Some(value) = get(key).filter(|value| !value.is_null())
+                && !ty.matches(value)
+            {
+                problems.push(Diagnostic::new(
+                    at(key),
+                    format!("`{key}` should be {ty}, found {value}"),
+                ));
+            }
+        }
+
+        for (key, allowed) in &self.allowed {
+            let values: Vec<&Value> = match get(key) {
+                Some(Value::Array(items)) => items.iter().collect(),
+                Some(value) => vec![value],
+                None => vec![],
+            };
+            for value in values {
+                let allowed_value = value
+                    .as_str()
+                    .is_some_and(|s| allowed.iter().any(|a| a == s));
+                if !allowed_value {
+                    problems.push(Diagnostic::new(
+                        at(key),
+                        format!(
+                            "{value} is not allowed for `{key}`, expected one of: {}",
+                            allowed.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if (self.min_date.is_some() || self.max_date.is_some())
+            && let Some(date) = get("date").and_then(Value::as_str).and_then(parse_date)
+            && (self.min_date.is_some_and(|min| date < min)
+                || self.max_date.is_some_and(|max| date > max))
+        {
+            let bound = |d: Option<NaiveDate>| d.map_or("…".to_string(), |d| d.to_string());
+            problems.push(Diagnostic::new(
+                at("date"),
+                format!(
+                    "`date` {date} is outside of {} to {}",
+                    bound(self.min_date),
+                    bound(self.max_date)
+                ),
+            ));
+        }
+
+        problems
+    }
+}
+
+/// Dates in a TOML config can be bare (`2024-01-01`) or quoted.
+fn deserialize_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<NaiveDate>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Date {
+        Toml(toml::value::Datetime),
+        String(String),
+    }
+
+    let s = match Date::deserialize(deserializer)? {
+        Date::Toml(date) => date.to_string(),
+        Date::String(s) => s,
+    };
+    parse_date(&s)
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid date {s:?}, expected YYYY-MM-DD")))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let schema: Schema = toml::from_str(
+            r#"
+            required = ["title", "date"]
+            min_date = 2020-01-01
+            types = { date = "date", weight = "integer" }
+            allowed = { tags = ["rust", "web"] }
+            "#,
+        )
+        .unwrap();
+        let lines = |key: &str| match key {
+            "date" => Some(Location::new(3, 7)),
+            "tags" => Some(Location::new(4, 7)),
+            "weight" => Some(Location::new(5, 9)),
+            _ => None,
+        };
+        let start = Location::line(2);
+        let check = |value| -> Vec<_> {
+            schema
+                .check(Some(&value), start, lines)
+                .into_iter()
+                .map(|d| (d.location.line, d.message))
+                .collect()
+        };
+
+        let ok = json!({ "title": "Hi", "date": "2024-05-01", "tags": ["rust"], "weight": 1 });
+        assert!(check(ok).is_empty());
+
+        let bad = json!({ "date": "2019-13-01", "tags": ["rust", "go"], "weight": "heavy" });
+        let problems = check(bad);
+        assert_eq!(
+            problems,
+            [
+                (2, "missing required key `title`".to_string()),
+                (
+                    3,
+                    "`date` should be a YYYY-MM-DD date, found \"2019-13-01\"".to_string()
+                ),
+                (
+                    5,
+                    "`weight` should be an integer, found \"heavy\"".to_string()
+                ),
+                (
+                    4,
+                    "\"go\" is not allowed for `tags`, expected one of: rust, web".to_string()
+                ),
+            ]
+        );
+
+        let early = json!({ "title": "Hi", "date": "2019-01-01" });
+        assert_eq!(
+            check(early),
+            [(
+                3,
+                "`date` 2019-01-01 is outside of 2020-01-01 to …".to_string()
+            )]
+        );
+
+        assert_eq!(schema.check(None, start, lines).len(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+//! `sitemap.xml`, following the [sitemaps protocol](https://www.sitemaps.org/protocol.html).
+
+use std::fmt::Write;
+
+use chrono::{DateTime, Utc};
+
+use crate::{html::escape, md::ParsedFile, url::absolute};
+
+#[derive(Debug, Clone, Default)]
+pub struct Sitemap {
+    /// Absolute URL of the site, every entry is resolved against it.
+    pub base_url: String,
+    pub entries: Vec<SitemapEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SitemapEntry {
+    /// Site-relative URL of the page.
+    pub url: String,
+    pub lastmod: DateTime<Utc>,
+}
+
+impl SitemapEntry {
+    pub fn new(page: &ParsedFile) -> Self {
+        SitemapEntry {
+            url: page.meta.url.clone(),
+            lastmod: page.updated(),
+        }
+    }
+}
+
+impl Sitemap {
+    pub fn xml(&self) -> String {
+        let mut out = String::new();
+        out.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        out.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+        for entry in &self.entries {
+            let _ = write!(
+                out,
+                "<url><loc>{}</loc><lastmod>{}</lastmod></url>",
+                escape(&absolute(&self.base_url, &entry.url)),
+                entry.lastmod.format("%Y-%m-%d"),
+            );
+        }
+        out.push_str("</urlset>");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_xml() {
+        let sitemap = Sitemap {
+            base_url: "https://example.com".into(),
+            entries: vec![SitemapEntry {
+                url: "/a&b/".into(),
+                lastmod: "2025-07-29T12:00:00Z".parse().unwrap(),
+            }],
+        };
+        assert!(sitemap.xml().contains(
+            "<url><loc>https://example.com/a&amp;b/</loc><lastmod>2025-07-29</lastmod></url>"
+        ));
+    }
+}
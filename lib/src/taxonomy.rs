@@ -0,0 +1,102 @@
+//! Taxonomies: ways of grouping pages by frontmatter terms, such as `tags` or `series`.
+//!
+//! A taxonomy named `tags` lists its terms at `/tags/`, and the pages of each term at
+//! `/tags/<term>/`. Terms are grouped by their [slug](crate::url::slugify), so `Rust` and
+//! `rust` are the same term.
+
+use std::collections::BTreeMap;
+
+use crate::{md::ParsedFile, url::slugify};
+
+#[derive(Debug)]
+pub struct Taxonomy<'p, 'c> {
+    pub name: String,
+    /// Terms ordered by slug.
+    pub terms: Vec<Term<'p, 'c>>,
+}
+
+#[derive(Debug)]
+pub struct Term<'p, 'c> {
+    /// The term as first spelled in a page.
+    pub name: String,
+    pub slug: String,
+    pub url: String,
+    /// The pages with this term, in the order they were given to [`Taxonomy::collect`].
+    pub pages: Vec<&'p ParsedFile<'c>>,
+}
+
+impl<'p, 'c> Taxonomy<'p, 'c> {
+    /// Group `pages` by their terms in the taxonomy `name`.
+    pub fn collect(name: &str, pages: &[&'p ParsedFile<'c>]) -> Self {
+        let url = Self::url_of(name);
+        let mut terms: BTreeMap<String, Term> = BTreeMap::new();
+        for &page in pages {
+            for term in page.terms(name) {
+                let slug = slugify(term);
+                if slug.is_empty() {
+                    continue;
+                }
+                let entry = terms.entry(slug.clone()).or_insert_with(|| Term {
+                    name: term.to_string(),
+                    url: format!("{url}{slug}/"),
+                    slug,
+                    pages: Vec::new(),
+                });
+                // A page listing the same term twice is still one page.
+                if !entry.pages.iter().any(|p| std::ptr::eq(*p, page)) {
+                    entry.pages.push(page);
+                }
+            }
+        }
+        Taxonomy {
+            name: name.to_string(),
+            terms: terms.into_values().collect(),
+        }
+    }
+
+    /// Site-relative URL of the page listing every term.
+    pub fn url(&self) -> String {
+        Self::url_of(&self.name)
+    }
+
+    fn url_of(name: &str) -> String {
+        format!("/{}/", slugify(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use comrak::{Arena, ComrakOptions};
+
+    use super::*;
+    use crate::md::MarkdownParser;
+
+    #[test]
+    fn test_collect() {
+        let arena = Arena::new();
+        let opts = ComrakOptions::default();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        let pages: Vec<_> = [
+            "---\ntags: [Rust, web]\n---\n",
+            "---\ntags: [rust, rust]\n---\n",
+            "# No tags",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, markdown)| parser.test_page(&format!("{i}.md"), markdown))
+        .collect();
+        let pages: Vec<_> = pages.iter().collect();
+
+        let tags = Taxonomy::collect("tags", &pages);
+        assert_eq!(tags.url(), "/tags/");
+        let summary: Vec<_> = tags
+            .terms
+            .iter()
+            .map(|term| (term.name.as_str(), term.url.as_str(), term.pages.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [("Rust", "/tags/rust/", 2), ("web", "/tags/web/", 1)]
+        );
+    }
+}
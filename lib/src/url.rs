@@ -0,0 +1,252 @@
+//! Page URLs and where they end up on disk.
+//!
+//! Every page gets a site-relative URL from a permalink pattern. The same URL decides the
+//! output path, so anything linking to a page only needs [`crate::md::ParsedFileMeta::url`].
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::Datelike;
+
+use crate::md::ParsedFile;
+
+/// Mirrors the source tree, with one directory per page.
+pub const DEFAULT_PERMALINK: &str = "/:path/:slug/";
+
+/// Permalink patterns.
+///
+/// Patterns are URL paths containing placeholders:
+///
+/// - `:path` - directory of the source file, relative to the source root
+/// - `:section` - first directory of `:path`
+/// - `:slug` - frontmatter `slug`, or the slugified file stem
+/// - `:title` - slugified frontmatter `title`, falling back to `:slug`
+/// - `:year`, `:month`, `:day` - frontmatter `date`, falling back to the file's modified date
+///
+/// Page bundles (`<dir>/index.md`) are treated as if they were a file named `<dir>`.
+///
+/// A pattern ending in `/` produces `<url>/index.html`; anything else is written as is.
+/// `.` and `..` segments are dropped, a URL cannot point outside the output directory. A
+/// `permalink` in the frontmatter that has any does not parse.
+#[derive(Debug, Clone)]
+pub struct Permalinks {
+    pub default: String,
+    /// Patterns for pages whose [section](crate::md::ParsedFileMeta::section) matches the key.
+    pub sections: HashMap<String, String>,
+}
+
+impl Default for Permalinks {
+    fn default() -> Self {
+        Permalinks {
+            default: DEFAULT_PERMALINK.to_string(),
+            sections: HashMap::new(),
+        }
+    }
+}
+
+impl Permalinks {
+    /// Fill in [`crate::md::ParsedFileMeta::url`] for every page.
+    pub fn apply(&self, pages: &mut [ParsedFile]) {
+        for page in pages {
+            page.meta.url = self.resolve(page);
+        }
+    }
+
+    /// The URL of `page`. A `permalink` in the frontmatter replaces the configured pattern.
+    pub fn resolve(&self, page: &ParsedFile) -> String {
+        let frontmatter = page.contents.frontmatter.as_ref();
+        let pattern = frontmatter
+            .and_then(|fm| fm.permalink.as_deref())
+            .or_else(|| self.sections.get(page.meta.section()).map(String::as_str))
+            .unwrap_or(&self.default);
+
+        // A bundle is named after its directory, so `posts/hello/index.md` is `hello` in
+        // `posts`.
+        let (dir, stem) = match page.meta.bundle_dir() {
+            Some(bundle) => (
+                bundle.parent(),
+                bundle.file_name().and_then(|s| s.to_str()).unwrap_or(""),
+            ),
+            None => (page.meta.rel_path.parent(), page.meta.stem()),
+        };
+
+        let slug = frontmatter
+            .and_then(|fm| fm.slug.as_deref())
+            .map(slugify)
+            .unwrap_or_else(|| slugify(stem));
+        let title = frontmatter
+            .and_then(|fm| fm.title.as_deref())
+            .map(slugify)
+            .unwrap_or_else(|| slug.clone());
+        let date = frontmatter
+            .and_then(|fm| fm.date)
+            .unwrap_or_else(|| page.meta.date.date_naive());
+        let path = dir
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_default();
+
+        let url = pattern
+            .replace(":section", page.meta.section())
+            .replace(":path", &path)
+            .replace(":slug", &slug)
+            .replace(":title", &title)
+            .replace(":year", &format!("{:04}", date.year()))
+            .replace(":month", &format!("{:02}", date.month()))
+            .replace(":day", &format!("{:02}", date.day()));
+
+        normalize(&url)
+    }
+}
+
+/// Where the page with `url` is written inside `output_dir`.
+///
+/// Only the plain segments of `url` are used, so the path never leaves `output_dir`.
+pub fn output_path(output_dir: &Path, url: &str) -> PathBuf {
+    let mut path = output_dir.to_path_buf();
+    path.extend(segments(url));
+    if url.ends_with('/') || path == output_dir {
+        path.push("index.html");
+    }
+    path
+}
+
+/// Turn the site-relative `url` into an absolute one below `base_url`.
+pub fn absolute(base_url: &str, url: &str) -> String {
+    format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        url.trim_start_matches('/')
+    )
+}
+
+/// Lowercase `s`, keeping only alphanumerics separated by single dashes.
+pub fn slugify(s: &str) -> String {
+    let mut slug = String::with_capacity(s.len());
+    for c in s.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// The segments of `url`, without empty, `.` and `..` ones.
+fn segments(url: &str) -> impl Iterator<Item = &str> {
+    url.split('/')
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+}
+
+/// Collapse empty segments left behind by empty placeholders, drop `.` and `..` and force a
+/// leading `/`.
+fn normalize(url: &str) -> String {
+    let trailing_slash = url.ends_with('/');
+    let mut normalized: String = segments(url).flat_map(|segment| ["/", segment]).collect();
+    if trailing_slash || normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use comrak::{Arena, ComrakOptions};
+
+    use super::*;
+    use crate::md::MarkdownParser;
+
+    fn resolve(permalinks: &Permalinks, rel_path: &str, markdown: &str) -> String {
+        let arena = Arena::new();
+        let opts = ComrakOptions::default();
+        let parser = MarkdownParser::with_arena(&arena, &opts);
+        permalinks.resolve(&parser.test_page(rel_path, markdown))
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut permalinks = Permalinks::default();
+        permalinks
+            .sections
+            .insert("posts".into(), "/:year/:month/:slug/".into());
+
+        assert_eq!(resolve(&permalinks, "intro.md", "# Hi"), "/intro/");
+        assert_eq!(
+            resolve(&permalinks, "pages/chapters/Chapter One.md", "# Hi"),
+            "/pages/chapters/chapter-one/"
+        );
+        assert_eq!(
+            resolve(
+                &permalinks,
+                "posts/hello.md",
+                "---\ndate: 2025-07-29\n---\n"
+            ),
+            "/2025/07/hello/"
+        );
+        assert_eq!(
+            resolve(&permalinks, "posts/hello.md", "# Hi"),
+            "/2024/01/hello/"
+        );
+        assert_eq!(
+            resolve(&permalinks, "posts/hello.md", "---\nslug: Greetings\n---\n"),
+            "/2024/01/greetings/"
+        );
+        assert_eq!(
+            resolve(
+                &permalinks,
+                "posts/hello.md",
+                "---\npermalink: /about.html\n---\n"
+            ),
+            "/about.html"
+        );
+        assert_eq!(
+            resolve(&permalinks, "pages/Trip Report/index.md", "# Hi"),
+            "/pages/trip-report/"
+        );
+        assert_eq!(
+            resolve(&permalinks, "posts/hello/index.md", "# Hi"),
+            "/2024/01/hello/"
+        );
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  Rust -- 2024 "), "rust-2024");
+        assert_eq!(slugify("Ünïcode Tags"), "ünïcode-tags");
+        assert_eq!(slugify("README"), "readme");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("//intro/"), "/intro/");
+        assert_eq!(normalize("/pages//ch1.html"), "/pages/ch1.html");
+        assert_eq!(normalize(""), "/");
+        assert_eq!(normalize("/../../escaped/"), "/escaped/");
+        assert_eq!(normalize("/a/./b/../c.html"), "/a/b/c.html");
+    }
+
+    #[test]
+    fn test_output_path() {
+        let out = Path::new("/out");
+        assert_eq!(output_path(out, "/"), PathBuf::from("/out/index.html"));
+        assert_eq!(
+            output_path(out, "/2025/07/intro/"),
+            PathBuf::from("/out/2025/07/intro/index.html")
+        );
+        assert_eq!(
+            output_path(out, "/feed.xml"),
+            PathBuf::from("/out/feed.xml")
+        );
+        assert_eq!(
+            output_path(out, "/../../escaped/"),
+            PathBuf::from("/out/escaped/index.html")
+        );
+        assert_eq!(output_path(out, "/.."), PathBuf::from("/out/index.html"));
+    }
+}
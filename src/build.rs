@@ -0,0 +1,762 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use color_eyre::Report;
+
+use aaska::{
+    fs::FileType,
+    html::OutputPaths,
+    md::{FrontmatterData, PageList, ParsedFile},
+    paginate::paginate,
+    sitemap::{Sitemap, SitemapEntry},
+    taxonomy::Taxonomy,
+};
+
+use crate::layouts::{LayoutContext, Layouts};
+use crate::prelude::*;
+use crate::{Config, SiteMetadata};
+
+/// Which source files need their pages rendered again.
+#[derive(Debug, Clone, Copy)]
+pub enum Scope<'a> {
+    Full,
+    /// Only these files changed. Listings are always regenerated, every page is when the list
+    /// of pages changed, and a change to anything but content or static files (templates, for
+    /// instance) means a full rebuild.
+    Changed(&'a [PathBuf]),
+}
+
+pub const STATIC_DIR: &str = "static";
+pub const SITEMAP_URL: &str = "/sitemap.xml";
+pub const ROBOTS_URL: &str = "/robots.txt";
+
+#[cfg(feature = "templates")]
+const WATCHED_SITE_DIRS: &[&str] = &[STATIC_DIR, crate::templates::TEMPLATES_DIR];
+#[cfg(not(feature = "templates"))]
+const WATCHED_SITE_DIRS: &[&str] = &[STATIC_DIR];
+
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    pub parsed: usize,
+    pub rendered: usize,
+    pub copied: usize,
+    pub removed: usize,
+    pub listings: usize,
+    pub elapsed: Duration,
+    /// Files skipped by a [`keep_going`](Config::keep_going) build, one error each.
+    pub failures: Vec<Report>,
+}
+
+impl BuildReport {
+    /// Fail with [`BuildFailures`] if any file was skipped.
+    pub fn check(self) -> Result<()> {
+        if self.failures.is_empty() {
+            Ok(())
+        } else {
+            Err(BuildFailures(self.failures).into())
+        }
+    }
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rendered {} page(s), {} listing(s), copied {} file(s), removed {} in {:.1?} \
+             ({} file(s) parsed)",
+            self.rendered, self.listings, self.copied, self.removed, self.elapsed, self.parsed
+        )?;
+        if !self.failures.is_empty() {
+            write!(f, ", {} file(s) failed", self.failures.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Every file a [`keep_going`](Config::keep_going) build skipped, and why.
+#[derive(Debug)]
+pub struct BuildFailures(pub Vec<Report>);
+
+impl fmt::Display for BuildFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for failure in &self.0 {
+            match failure.downcast_ref::<aaska::Error>() {
+                Some(aaska::Error::Parse(diagnostics)) => {
+                    for diagnostic in &diagnostics.0 {
+                        write!(f, "{diagnostic}\n\n")?;
+                    }
+                }
+                _ => write!(f, "error: {failure:#}\n\n")?,
+            }
+        }
+        write!(f, "{} file(s) failed to build", self.0.len())
+    }
+}
+
+impl std::error::Error for BuildFailures {}
+
+/// Renders the site from `config.source_dir` into `config.output_dir`.
+///
+/// Keeps track of what the previous build wrote, so outputs of pages that were deleted or
+/// changed their URL are removed on the next build.
+pub struct Builder<'a> {
+    config: &'a Config<'a>,
+    meta: &'a SiteMetadata,
+    layouts: Layouts,
+    previous: OutputPaths,
+    /// The pages of the previous build, as every page sees them.
+    previous_pages: Vec<ListedPage>,
+}
+
+/// What a page shows of the other pages: [`LayoutContext::neighbours`], the listings and the
+/// `pages` of templates read their title, URL, date and frontmatter, tags included.
+#[derive(PartialEq)]
+struct ListedPage {
+    path: PathBuf,
+    url: String,
+    title: String,
+    date: DateTime<Utc>,
+    frontmatter: Option<FrontmatterData>,
+}
+
+fn listed_pages(pages: &PageList) -> Vec<ListedPage> {
+    pages
+        .sorted_by_date()
+        .into_iter()
+        .map(|page| ListedPage {
+            path: page.meta.path.clone(),
+            url: page.meta.url.clone(),
+            title: page.title().to_string(),
+            date: page.date(),
+            frontmatter: page.contents.frontmatter.clone(),
+        })
+        .collect()
+}
+
+impl<'a> Builder<'a> {
+    pub fn new(config: &'a Config<'a>, meta: &'a SiteMetadata) -> Self {
+        Builder {
+            config,
+            meta,
+            layouts: Layouts::new(config.layouts.clone(), &config.search_path),
+            previous: OutputPaths::default(),
+            previous_pages: Vec::new(),
+        }
+    }
+
+    /// Directories whose changes affect the build: the source directory, and the static and
+    /// template directories of the site and its theme.
+    pub fn watched_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.config.source_dir.clone()];
+        for dir in WATCHED_SITE_DIRS {
+            dirs.extend(self.config.search_path.existing_dirs(Path::new(dir)));
+        }
+        dirs
+    }
+
+    /// Render the pages in `scope`, plus every page that lists other pages.
+    ///
+    /// All sources are parsed regardless of `scope`, since listings need the frontmatter of
+    /// every page.
+    pub fn build(&mut self, scope: Scope) -> Result<BuildReport> {
+        let start = Instant::now();
+        let config = self.config;
+        let mut report = BuildReport::default();
+
+        let static_dirs = config.search_path.existing_dirs(Path::new(STATIC_DIR));
+        let changed: Option<HashSet<&Path>> = match scope {
+            Scope::Changed(paths)
+                if paths.iter().all(|path| {
+                    path.starts_with(&config.source_dir)
+                        || static_dirs.iter().any(|dir| path.starts_with(dir))
+                }) =>
+            {
+                Some(paths.iter().map(PathBuf::as_path).collect())
+            }
+            _ => None,
+        };
+        if changed.is_none() {
+            // Templates are cached once loaded, start over in case they changed.
+            self.layouts = Layouts::new(config.layouts.clone(), &config.search_path);
+        }
+
+        let post_list =
+            aaska::fs::list_files_dir_rec_with(&config.source_dir, &config.content_scan)
+                .wrap_err("Failed to list source directory")?;
+
+        let arena = aaska::comrak::Arena::new();
+        let parser = aaska::md::MarkdownParser::with_arena(&arena, &config.comrak_options)
+            .with_schemas(config.schemas.clone());
+        let mut parsed = if config.keep_going {
+            let (parsed, errors) = parser.parse_many_keep_going(&post_list);
+            report.failures.extend(errors.into_iter().map(Report::from));
+            parsed
+        } else {
+            parser.parse_many(&post_list)?
+        };
+        report.parsed = parsed.len();
+        let parsed_paths: HashSet<&Path> =
+            parsed.iter().map(|page| page.meta.path.as_path()).collect();
+        let unparsed: Vec<&Path> = post_list
+            .iter()
+            .filter(|file| file.file_type != FileType::Unsupported)
+            .map(|file| file.path.as_path())
+            .filter(|path| !parsed_paths.contains(path))
+            .collect();
+
+        // Drop unpublished pages here, once, so that no listing, feed or sitemap sees them.
+        let today = chrono::Utc::now().date_naive();
+        let mut hidden_bundles: HashSet<PathBuf> = HashSet::new();
+        parsed.retain(|page| {
+            let visible = !page.is_expired(today)
+                && (config.drafts || !(page.is_draft() || page.is_scheduled(today)));
+            if !visible {
+                debug!("Skipping unpublished page: {}", page.meta.path.display());
+                hidden_bundles.extend(page.meta.bundle_dir().map(Path::to_path_buf));
+            }
+            visible
+        });
+        config.permalinks.apply(&mut parsed);
+
+        let mut outputs = OutputPaths::default();
+        let index_path = aaska::url::output_path(&config.output_dir, "/");
+        outputs.claim(&index_path, "the index page")?;
+
+        let static_files = config
+            .search_path
+            .files(Path::new(STATIC_DIR), &config.static_scan)?;
+        for (rel_path, src) in &static_files {
+            if config.static_ignore.is_match(rel_path) {
+                trace!("Ignoring static file: {}", src.display());
+                continue;
+            }
+            let dest = config.output_dir.join(STATIC_DIR).join(rel_path);
+            outputs.claim(&dest, src.display())?;
+            if aaska::fs::copy_if_changed(src, &dest)? {
+                info!("Copied {}", Path::new(STATIC_DIR).join(rel_path).display());
+                report.copied += 1;
+            }
+        }
+
+        for file in &parsed {
+            let dest_path = aaska::url::output_path(&config.output_dir, &file.meta.url);
+            outputs.claim(&dest_path, file.meta.path.display())?;
+        }
+        // A page that no longer parses keeps its previous output until it is fixed, unless
+        // another page took its place.
+        for source in &unparsed {
+            let source = source.display().to_string();
+            for dest in self.previous.claimed_by(&source) {
+                if !outputs.contains(dest) {
+                    outputs.claim(dest, &source)?;
+                    outputs.mark_written(dest);
+                }
+            }
+        }
+
+        // Assets of a page bundle go next to the page, wherever its URL puts it, so relative
+        // references keep working. Other assets mirror their place in the source tree.
+        let bundles: HashMap<&Path, &str> = parsed
+            .iter()
+            .filter_map(|page| Some((page.meta.bundle_dir()?, page.meta.url.as_str())))
+            .collect();
+        let assets = post_list
+            .iter()
+            .filter(|file| file.file_type == FileType::Unsupported);
+        for asset in assets {
+            let mut ancestors = asset.rel_path.ancestors().skip(1);
+            if ancestors.clone().any(|dir| hidden_bundles.contains(dir)) {
+                continue;
+            }
+            let bundle = ancestors.find_map(|dir| Some((dir, *bundles.get(dir)?)));
+            let dest = match bundle {
+                Some((bundle_dir, url)) => {
+                    let page_dest = aaska::url::output_path(&config.output_dir, url);
+                    let page_dir = page_dest.parent().unwrap_or(&config.output_dir);
+                    page_dir.join(asset.rel_path.strip_prefix(bundle_dir)?)
+                }
+                None => config.output_dir.join(&asset.rel_path),
+            };
+            outputs.claim(&dest, asset.path.display())?;
+            if aaska::fs::copy_if_changed(&asset.path, &dest)? {
+                report.copied += 1;
+            }
+        }
+
+        let mut pages = PageList::from(parsed);
+        // Every page links to others, so once a page is added, removed, moved, renamed,
+        // re-dated or re-tagged they all need rendering again.
+        let changed = changed.filter(|_| listed_pages(&pages) == self.previous_pages);
+        let unwritten = self.render_pages(&pages, changed.as_ref(), &mut outputs, &mut report)?;
+        if !unwritten.is_empty() {
+            // Nothing may link to a page that was never written: leave it out, and render the
+            // others again without it. Their failures were reported the first time.
+            pages = PageList::from(
+                pages
+                    .files
+                    .into_iter()
+                    .filter(|file| !unwritten.contains(&file.meta.path))
+                    .collect::<Vec<_>>(),
+            );
+            let mut retry = BuildReport::default();
+            self.render_pages(&pages, None, &mut outputs, &mut retry)?;
+            report.rendered = retry.rendered;
+        }
+
+        for file in &pages.files {
+            for alias in file.aliases() {
+                let dest = aaska::url::output_path(&config.output_dir, alias);
+                outputs.claim(
+                    &dest,
+                    format!("the alias {alias} of {}", file.meta.path.display()),
+                )?;
+                write_output(&dest, aaska::html::redirect(&file.meta.url))?;
+            }
+        }
+
+        // Listings go in the sitemap too, dated by their newest page in the sitemap.
+        let mut listings: Vec<SitemapEntry> = Vec::new();
+
+        let listed = pages.sorted_by_date();
+        let newest_first: Vec<_> = listed.iter().rev().copied().collect();
+        outputs.check_links(
+            &config.output_dir,
+            "the index page",
+            newest_first.iter().copied(),
+        )?;
+        for pager in paginate("/", &newest_first, config.paginate) {
+            let dest = aaska::url::output_path(&config.output_dir, &pager.url);
+            // The first page was claimed up front, before anything could take its place.
+            if dest != index_path {
+                outputs.claim(&dest, format!("page {} of the index", pager.number))?;
+            }
+            write_output(&dest, self.layouts.render_index(self.meta, &pager)?)?;
+            report.listings += 1;
+            listings.extend(listing_entry(&pager.url, pager.items.iter().copied()));
+        }
+
+        for name in &config.taxonomies {
+            let taxonomy = Taxonomy::collect(name, &newest_first);
+            let dest = aaska::url::output_path(&config.output_dir, &taxonomy.url());
+            outputs.claim(&dest, format!("the {name} page"))?;
+            write_output(&dest, self.layouts.render_taxonomy(self.meta, &taxonomy)?)?;
+            report.listings += 1;
+            listings.extend(listing_entry(
+                &taxonomy.url(),
+                taxonomy
+                    .terms
+                    .iter()
+                    .flat_map(|term| term.pages.iter().copied()),
+            ));
+
+            for term in &taxonomy.terms {
+                outputs.check_links(&config.output_dir, &term.url, term.pages.iter().copied())?;
+                for pager in paginate(&term.url, &term.pages, config.paginate) {
+                    let dest = aaska::url::output_path(&config.output_dir, &pager.url);
+                    outputs.claim(
+                        &dest,
+                        format!(
+                            "page {} of the {name} page for {:?}",
+                            pager.number, term.name
+                        ),
+                    )?;
+                    let html = self
+                        .layouts
+                        .render_term(self.meta, &taxonomy, term, &pager)?;
+                    write_output(&dest, html)?;
+                    report.listings += 1;
+                    listings.extend(listing_entry(&pager.url, pager.items.iter().copied()));
+                }
+            }
+        }
+
+        for (url, feed) in crate::feeds::render(config, self.meta, &newest_first)? {
+            let dest = aaska::url::output_path(&config.output_dir, &url);
+            outputs.claim(&dest, format!("the feed {url}"))?;
+            write_output(&dest, feed)?;
+            report.listings += 1;
+        }
+
+        let sitemap_url = config
+            .sitemap
+            .then(|| aaska::url::absolute(&self.meta.base_url, SITEMAP_URL));
+        if config.sitemap {
+            let mut sitemap = Sitemap {
+                base_url: self.meta.base_url.clone(),
+                entries: listings,
+            };
+            sitemap.entries.extend(
+                listed
+                    .iter()
+                    .filter(|page| page.in_sitemap())
+                    .map(|page| SitemapEntry::new(page)),
+            );
+            let dest = aaska::url::output_path(&config.output_dir, SITEMAP_URL);
+            outputs.claim(&dest, "the sitemap")?;
+            write_output(&dest, sitemap.xml())?;
+        }
+        if config.robots.enabled {
+            let dest = aaska::url::output_path(&config.output_dir, ROBOTS_URL);
+            outputs.claim(&dest, "robots.txt")?;
+            write_output(&dest, config.robots.render(sitemap_url.as_deref()))?;
+        }
+
+        for stale in self.previous.paths().filter(|path| !outputs.contains(path)) {
+            if stale.is_file() {
+                std::fs::remove_file(stale)
+                    .wrap_err_with(|| format!("Failed to remove output: {}", stale.display()))?;
+                report.removed += 1;
+            }
+        }
+        self.previous = outputs;
+        self.previous_pages = listed_pages(&pages);
+
+        report.elapsed = start.elapsed();
+        Ok(report)
+    }
+
+    /// Render every page of `pages` whose source is in `changed` (all of them if `None`), or
+    /// whose output is missing.
+    ///
+    /// In a [`keep_going`](Config::keep_going) build, returns the sources of the pages that
+    /// failed and have no previous output to fall back on.
+    fn render_pages(
+        &self,
+        pages: &PageList,
+        changed: Option<&HashSet<&Path>>,
+        outputs: &mut OutputPaths,
+        report: &mut BuildReport,
+    ) -> Result<HashSet<PathBuf>> {
+        let mut unwritten = HashSet::new();
+        for file in &pages.files {
+            let dest_path = aaska::url::output_path(&self.config.output_dir, &file.meta.url);
+
+            // Pages that are new or moved must be written even if their source is unchanged.
+            let unchanged =
+                changed.is_some_and(|changed| !changed.contains(file.meta.path.as_path()));
+            if unchanged && self.previous.is_written(&dest_path) {
+                outputs.mark_written(&dest_path);
+                continue;
+            }
+
+            match self.render_page(file, pages, &dest_path) {
+                Ok(()) => {
+                    outputs.mark_written(&dest_path);
+                    report.rendered += 1;
+                }
+                // The previous output, if any, stays in place.
+                Err(e) if self.config.keep_going => {
+                    if self.previous.is_written(&dest_path) {
+                        outputs.mark_written(&dest_path);
+                    } else {
+                        unwritten.insert(file.meta.path.clone());
+                    }
+                    report.failures.push(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(unwritten)
+    }
+
+    fn render_page<'c>(
+        &self,
+        file: &ParsedFile<'c>,
+        pages: &PageList<'c>,
+        dest: &Path,
+    ) -> Result<()> {
+        let generated_file = aaska::html::generate_html(file, &self.config.comrak_options)?;
+        let html = self.layouts.render(&LayoutContext {
+            page: file,
+            body: &generated_file.contents,
+            site: self.meta,
+            pages,
+        })?;
+
+        write_output(dest, html).wrap_err_with(|| {
+            format!(
+                "Failed to write HTML for file: {}",
+                file.meta.path.display()
+            )
+        })
+    }
+}
+
+/// The sitemap entry of the listing at `url`, or `None` if none of its `pages` is in the
+/// sitemap.
+fn listing_entry<'p, 'c: 'p>(
+    url: &str,
+    pages: impl IntoIterator<Item = &'p ParsedFile<'c>>,
+) -> Option<SitemapEntry> {
+    let lastmod = pages
+        .into_iter()
+        .filter(|page| page.in_sitemap())
+        .map(|page| page.updated())
+        .max()?;
+    Some(SitemapEntry {
+        url: url.to_string(),
+        lastmod,
+    })
+}
+
+fn write_output(dest: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(dest, contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::{LoadedConfig, SiteConfig};
+
+    /// Write `files` to `content/` in a fresh site directory, returning the directory.
+    fn site(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("aaska-build-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (rel_path, contents) in files {
+            let path = root.join("content").join(rel_path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    /// Build the site at `root` into `root/public`.
+    fn build(root: &Path, site: SiteConfig) -> Result<BuildReport> {
+        let loaded = LoadedConfig {
+            site,
+            root: root.to_path_buf(),
+            theme_dir: None,
+        };
+        let (config, meta) = loaded.into_config(None, None)?;
+        Builder::new(&config, &meta).build(Scope::Full)
+    }
+
+    fn read(root: &Path, rel_path: &str) -> String {
+        std::fs::read_to_string(root.join("public").join(rel_path)).unwrap()
+    }
+
+    #[test]
+    fn test_root_index() {
+        let root = site(
+            "root-index",
+            &[
+                ("index.md", "---\ntitle: Welcome\n---\n# Welcome"),
+                ("posts/hello/index.md", "---\ntitle: Hello\n---\n# Hello"),
+                ("posts/hello/cover.png", "png"),
+                ("stray.png", "png"),
+            ],
+        );
+        build(&root, SiteConfig::default()).unwrap();
+
+        // A plain page, not a bundle taking the place of the index page.
+        assert!(read(&root, "index/index.html").contains("Welcome"));
+        let index = read(&root, "index.html");
+        assert!(index.contains(r#"href="/index/""#), "{index}");
+        assert!(index.contains(r#"href="/posts/hello/""#), "{index}");
+        assert_eq!(read(&root, "posts/hello/cover.png"), "png");
+        assert_eq!(read(&root, "stray.png"), "png");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_scan_options() {
+        let root = site(
+            "scan-options",
+            &[
+                ("post.md", "---\ntitle: Post\n---\n# Post"),
+                (".git/notes.md", "---\ntitle: Notes\n---\n# Notes"),
+            ],
+        );
+        for file in [
+            ".well-known/security.txt",
+            ".htaccess",
+            ".DS_Store",
+            ".aaskaignore",
+            ".git/HEAD",
+            "style.css",
+            "style.css.swp",
+        ] {
+            let path = root.join(STATIC_DIR).join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+        let site = SiteConfig {
+            include: vec!["*.md".to_string()],
+            exclude: vec!["*.swp".to_string()],
+            hidden: true,
+            static_allow_hidden: vec![".well-known".to_string(), ".htaccess".to_string()],
+            ..Default::default()
+        };
+        build(&root, site).unwrap();
+
+        // `include` is for content only, `exclude` for both, and `.git` is never read.
+        let output = root.join("public");
+        assert!(output.join("post/index.html").exists());
+        let index = read(&root, "index.html");
+        assert!(!index.contains("Notes"), "{index}");
+        for file in [".well-known/security.txt", ".htaccess", "style.css"] {
+            assert!(output.join(STATIC_DIR).join(file).exists(), "{file}");
+        }
+        for file in [".DS_Store", ".aaskaignore", ".git", "style.css.swp"] {
+            assert!(!output.join(STATIC_DIR).join(file).exists(), "{file}");
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_keep_going() {
+        let root = site(
+            "keep-going",
+            &[
+                ("a.md", "---\ntitle: A\ndate: 2020-01-01\n---\n# A"),
+                ("b.md", "---\ntitle: B\ndate: 2021-01-01\n---\n# B"),
+            ],
+        );
+        let loaded = LoadedConfig {
+            site: SiteConfig::default(),
+            root: root.clone(),
+            theme_dir: None,
+        };
+        let (mut config, meta) = loaded.into_config(None, None).unwrap();
+        config.keep_going = true;
+        let mut builder = Builder::new(&config, &meta);
+        builder.build(Scope::Full).unwrap().check().unwrap();
+
+        // A page that stops parsing keeps its previous output.
+        let a = root.join("content/a.md");
+        std::fs::write(&a, "---\ntitle: A\ndate: soon\n---\n# A").unwrap();
+        let report = builder.build(Scope::Changed(&[a])).unwrap();
+        assert_eq!(report.failures.len(), 1);
+        assert!(read(&root, "a/index.html").contains("A"));
+
+        // A new page that fails to render is linked from nowhere.
+        let c = root.join("content/c.md");
+        std::fs::write(
+            &c,
+            "---\ntitle: C\ndate: 2022-01-01\nlayout: nope\n---\n# C",
+        )
+        .unwrap();
+        let report = builder.build(Scope::Changed(&[c])).unwrap();
+        assert_eq!(report.failures.len(), 2);
+        assert!(!root.join("public/c/index.html").exists());
+        for page in ["index.html", "b/index.html", "tags/index.html"] {
+            let html = read(&root, page);
+            assert!(!html.contains(r#"href="/c/""#), "{page}: {html}");
+        }
+        assert!(!read(&root, "rss.xml").contains("/c/"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_aliases() {
+        let root = site(
+            "aliases",
+            &[(
+                "hello.md",
+                "---\naliases: [/2020/hello/, /hi.html]\n---\n# Hello",
+            )],
+        );
+        build(&root, SiteConfig::default()).unwrap();
+
+        for alias in ["2020/hello/index.html", "hi.html"] {
+            let redirect = read(&root, alias);
+            assert!(
+                redirect.contains(r#"content="0; url=/hello/""#),
+                "{redirect}"
+            );
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_index_newest_first() {
+        let root = site(
+            "index-pages",
+            &[
+                ("old.md", "---\ndate: 2020-01-01\n---\n# Old"),
+                ("new.md", "---\ndate: 2025-01-01\n---\n# New"),
+            ],
+        );
+        let site = SiteConfig {
+            paginate: Some(1),
+            ..SiteConfig::default()
+        };
+        build(&root, site).unwrap();
+
+        // Untitled pages are listed by their file name.
+        let first = read(&root, "index.html");
+        assert!(first.contains(r#"<a href="/new/">new</a>"#), "{first}");
+        assert!(!first.contains(r#"href="/old/""#), "{first}");
+        let second = read(&root, "page/2/index.html");
+        assert!(second.contains(r#"href="/old/""#), "{second}");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_term_pages_newest_first() {
+        let root = site(
+            "term-pages",
+            &[
+                ("old.md", "---\ndate: 2020-01-01\ntags: [rust]\n---\n# Old"),
+                ("new.md", "---\ndate: 2025-01-01\ntags: [rust]\n---\n# New"),
+            ],
+        );
+        let site = SiteConfig {
+            paginate: Some(1),
+            ..SiteConfig::default()
+        };
+        build(&root, site).unwrap();
+
+        let first = read(&root, "tags/rust/index.html");
+        assert!(first.contains(r#"href="/new/""#), "{first}");
+        assert!(!first.contains(r#"href="/old/""#), "{first}");
+        let second = read(&root, "tags/rust/page/2/index.html");
+        assert!(second.contains(r#"href="/old/""#), "{second}");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_retagged_page() {
+        let root = site(
+            "retagged",
+            &[
+                ("a.md", "---\ndate: 2020-01-01\ntags: [old]\n---\n# A"),
+                ("b.md", "---\ndate: 2021-01-01\ntags: [old]\n---\n# B"),
+            ],
+        );
+        let loaded = LoadedConfig {
+            site: SiteConfig::default(),
+            root: root.clone(),
+            theme_dir: None,
+        };
+        let (config, meta) = loaded.into_config(None, None).unwrap();
+        let mut builder = Builder::new(&config, &meta);
+        builder.build(Scope::Full).unwrap();
+
+        // Other pages may show the tags of every page, so they are all rendered again.
+        let a = root.join("content/a.md");
+        std::fs::write(&a, "---\ndate: 2020-01-01\ntags: [new]\n---\n# A").unwrap();
+        let report = builder.build(Scope::Changed(&[a])).unwrap();
+        assert_eq!(report.rendered, 2);
+        let term = read(&root, "tags/new/index.html");
+        assert!(term.contains(r#"href="/a/""#), "{term}");
+        let term = read(&root, "tags/old/index.html");
+        assert!(!term.contains(r#"href="/a/""#), "{term}");
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -15,6 +15,9 @@ struct RawArgs {
     verbosity: u8,
     #[arg(long, global = true, default_value = "false")]
     no_color: bool,
+    /// Path to the site config file. Defaults to the nearest `aaska.toml`.
+    #[arg(long, short = 'c', global = true)]
+    config: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Clone, Debug)]
@@ -25,12 +28,44 @@ pub enum RawCommand {
         input: Option<PathBuf>,
         #[arg(short, long)]
         output: Option<PathBuf>,
+        /// Include drafts and pages scheduled for later.
+        #[arg(long)]
+        drafts: bool,
+        /// Skip pages that fail to build, and list every failure at the end.
+        #[arg(long)]
+        keep_going: bool,
+    },
+    /// Build the site and rebuild changed pages until interrupted.
+    Watch {
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Include drafts and pages scheduled for later.
+        #[arg(long)]
+        drafts: bool,
+        /// Skip pages that fail to build, and list every failure at the end.
+        #[arg(long)]
+        keep_going: bool,
+    },
+    /// Build the site, serve it locally and rebuild on changes. Drafts are included.
+    Serve {
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// Defaults to a temporary directory, removed on exit.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+        #[arg(long, default_value = "1111")]
+        port: u16,
     },
 }
 
 #[derive(Debug)]
 pub(crate) struct ParsedArgs {
     pub command: Command,
+    pub config_path: Option<PathBuf>,
     pub tracing_options: TracingOptions,
 }
 
@@ -46,13 +81,44 @@ impl ParsedArgs {
 
         let command = match args.command {
             RawCommand::Sample => Command::Sample,
-            RawCommand::Generate { input, output } => {
-                Command::Generate(GenerateArgs { input, output })
-            }
+            RawCommand::Generate {
+                input,
+                output,
+                drafts,
+                keep_going,
+            } => Command::Generate(GenerateArgs {
+                input,
+                output,
+                drafts,
+                keep_going,
+            }),
+            RawCommand::Watch {
+                input,
+                output,
+                drafts,
+                keep_going,
+            } => Command::Watch(GenerateArgs {
+                input,
+                output,
+                drafts,
+                keep_going,
+            }),
+            RawCommand::Serve {
+                input,
+                output,
+                host,
+                port,
+            } => Command::Serve(ServeArgs {
+                input,
+                output,
+                host,
+                port,
+            }),
         };
 
         ParsedArgs {
             command,
+            config_path: args.config,
             tracing_options: TracingOptions {
                 log_level,
                 pretty_print: args.pretty_print,
@@ -70,10 +136,22 @@ impl ParsedArgs {
 pub enum Command {
     Sample,
     Generate(GenerateArgs),
+    Watch(GenerateArgs),
+    Serve(ServeArgs),
 }
 
 #[derive(Debug)]
 pub struct GenerateArgs {
     pub input: Option<PathBuf>,
     pub output: Option<PathBuf>,
+    pub drafts: bool,
+    pub keep_going: bool,
+}
+
+#[derive(Debug)]
+pub struct ServeArgs {
+    pub input: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub host: String,
+    pub port: u16,
 }
@@ -1,2 +1,4 @@
 pub mod generate;
 pub mod sample;
+pub mod serve;
+pub mod watch;
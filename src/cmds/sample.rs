@@ -31,12 +31,13 @@ This is a code block in Rust.
         .join(std::process::id().to_string());
 
     // Define nested structure
+    let content = root.join("content");
     let structure = vec![
-        ("example.md", &root),
-        ("pages/intro.md", &root),
-        ("pages/chapters/ch1.md", &root),
-        ("pages/chapters/ch2.md", &root),
-        ("pages2/woop/README.md", &root),
+        ("example.md", &content),
+        ("pages/intro.md", &content),
+        ("pages/chapters/ch1.md", &content),
+        ("pages/chapters/ch2.md", &content),
+        ("pages2/woop/README.md", &content),
     ];
     for (file_path, base_dir) in structure {
         let full_path = base_dir.join(file_path);
@@ -54,6 +55,15 @@ This is a code block in Rust.
         std::fs::write(&full_path, md_with_frontmatter)?;
     }
 
+    std::fs::write(
+        root.join(crate::config::CONFIG_FILE_NAME),
+        r#"title = "Aaska sample"
+author = "druskus"
+source_dir = "content"
+output_dir = "public"
+"#,
+    )?;
+
     info!("Sample source files generated in: {}", root.display());
 
     Ok(())
@@ -0,0 +1,207 @@
+use std::{
+    io::Write,
+    path::{Component, Path, PathBuf},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+};
+
+use tiny_http::{Header, Request, Response, Server};
+
+pub use crate::prelude::*;
+use crate::{
+    build::{Builder, Scope},
+    config::LoadedConfig,
+};
+
+const RELOAD_ENDPOINT: &str = "/__aaska/reload";
+
+const RELOAD_SNIPPET: &str = r#"<script>
+new EventSource("/__aaska/reload").onmessage = () => location.reload();
+</script>"#;
+
+pub fn serve(config_path: Option<PathBuf>, args: crate::cli::ServeArgs) -> Result<()> {
+    let mut _temp_output = None;
+    let output = match args.output {
+        Some(output) => output,
+        None => {
+            let output = std::env::temp_dir()
+                .join("aaska-serve")
+                .join(std::process::id().to_string());
+            remove_on_signal(output.clone())?;
+            _temp_output = Some(TempOutput(output.clone()));
+            output
+        }
+    };
+    let (mut config, meta) =
+        LoadedConfig::load(config_path.as_deref())?.into_config(args.input, Some(output))?;
+    config.drafts = true;
+
+    crate::validate_config(&config)?;
+    crate::canonicalize_dirs(&mut config)?;
+
+    let mut builder = Builder::new(&config, &meta);
+    builder.build(Scope::Full)?;
+
+    let server = Server::http((args.host.as_str(), args.port))
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to start server: {e}"))?;
+    info!(
+        "Serving {} at http://{}:{}/",
+        config.output_dir.display(),
+        args.host,
+        args.port
+    );
+
+    let clients = Clients::default();
+    {
+        let clients = clients.clone();
+        let root = config.output_dir.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let clients = clients.clone();
+                let root = root.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = handle(request, &root, &clients) {
+                        debug!("Request failed: {e}");
+                    }
+                });
+            }
+        });
+    }
+
+    crate::watcher::watch(&builder.watched_dirs(), &config.output_dir, |changed| {
+        let report = builder.build(Scope::Changed(&changed))?;
+        info!("Rebuilt: {report}");
+        clients.reload();
+        Ok(())
+    })
+}
+
+/// The default output directory, removed when `serve` returns.
+struct TempOutput(PathBuf);
+
+impl Drop for TempOutput {
+    fn drop(&mut self) {
+        remove_output(&self.0);
+    }
+}
+
+fn remove_output(dir: &Path) {
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => debug!("Removed {}", dir.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove {}: {e}", dir.display()),
+    }
+}
+
+/// Remove `dir` and exit on Ctrl-C or SIGTERM, which would otherwise end the process without
+/// running [`TempOutput`]'s destructor.
+fn remove_on_signal(dir: PathBuf) -> Result<()> {
+    ctrlc::set_handler(move || {
+        remove_output(&dir);
+        std::process::exit(130);
+    })
+    .wrap_err("Failed to set the Ctrl-C handler")
+}
+
+/// Browsers currently listening for reload events.
+#[derive(Clone, Default)]
+struct Clients(Arc<Mutex<Vec<Sender<()>>>>);
+
+impl Clients {
+    fn subscribe(&self) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel();
+        self.0.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn reload(&self) {
+        // Listeners whose connection is gone have dropped their receiver.
+        self.0.lock().unwrap().retain(|tx| tx.send(()).is_ok());
+    }
+}
+
+fn handle(request: Request, root: &Path, clients: &Clients) -> std::io::Result<()> {
+    let url = request
+        .url()
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("/")
+        .to_string();
+
+    if url == RELOAD_ENDPOINT {
+        let events = clients.subscribe();
+        let mut writer = request.into_writer();
+        writer.write_all(
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Type: text/event-stream\r\n\
+              Cache-Control: no-cache\r\n\
+              Connection: keep-alive\r\n\r\n",
+        )?;
+        writer.flush()?;
+        while events.recv().is_ok() {
+            writer.write_all(b"data: reload\n\n")?;
+            writer.flush()?;
+        }
+        return Ok(());
+    }
+
+    let Some(path) = resolve(root, &url) else {
+        return request.respond(Response::from_string("404 Not Found").with_status_code(404));
+    };
+
+    let mut body = std::fs::read(&path)?;
+    let content_type = content_type(&path);
+    if content_type.starts_with("text/html") {
+        body = inject_reload(body);
+    }
+
+    let header = Header::from_bytes("Content-Type", content_type).expect("valid header");
+    request.respond(Response::from_data(body).with_header(header))
+}
+
+/// Map a request path onto a file inside `root`, refusing to leave it.
+fn resolve(root: &Path, url: &str) -> Option<PathBuf> {
+    let mut path = root.to_path_buf();
+    for component in Path::new(url.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+
+    if path.is_dir() {
+        path.push("index.html");
+    }
+    path.is_file().then_some(path)
+}
+
+fn inject_reload(body: Vec<u8>) -> Vec<u8> {
+    let mut html = String::from_utf8_lossy(&body).into_owned();
+    match html.rfind("</body>") {
+        Some(pos) => html.insert_str(pos, RELOAD_SNIPPET),
+        None => html.push_str(RELOAD_SNIPPET),
+    }
+    html.into_bytes()
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css",
+        Some("js") => "text/javascript",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,32 @@
+use std::path::PathBuf;
+
+pub use crate::prelude::*;
+use crate::{
+    build::{Builder, Scope},
+    config::LoadedConfig,
+};
+
+pub fn watch(config_path: Option<PathBuf>, args: crate::cli::GenerateArgs) -> Result<()> {
+    let loaded = LoadedConfig::load(config_path.as_deref())?;
+    loaded.check_base_url();
+    let (mut config, meta) = loaded.into_config(args.input, args.output)?;
+    config.drafts = args.drafts;
+    config.keep_going = args.keep_going;
+
+    crate::validate_config(&config)?;
+    crate::canonicalize_dirs(&mut config)?;
+
+    let mut builder = Builder::new(&config, &meta);
+    let report = builder.build(Scope::Full)?;
+    info!("Initial build: {report}");
+    // Keep watching, the failing files are rebuilt once they change.
+    if let Err(e) = report.check() {
+        error!("{}", crate::render_error(&e));
+    }
+
+    crate::watcher::watch(&builder.watched_dirs(), &config.output_dir, |changed| {
+        let report = builder.build(Scope::Changed(&changed))?;
+        info!("Rebuilt: {report}");
+        report.check()
+    })
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use aaska::{
+    comrak::{ComrakOptions, ExtensionOptions, ParseOptions, RenderOptions},
+    fs::ScanOptions,
+    schema::{Schema, Schemas},
+    url::{Permalinks, DEFAULT_PERMALINK},
+};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+
+use crate::prelude::*;
+use crate::theme::{self, SearchPath};
+
+pub const CONFIG_FILE_NAME: &str = "aaska.toml";
+
+/// Contents of a site's `aaska.toml`.
+///
+/// Every key is optional. Relative directories are resolved against the directory the file
+/// was found in.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SiteConfig {
+    /// Name of a theme in the `themes/` directory, see [`crate::theme`].
+    pub theme: Option<String>,
+    pub title: String,
+    pub author: String,
+    /// Where the site is published, like `https://example.com/`. Unset means `/`.
+    pub base_url: Option<String>,
+    pub source_dir: PathBuf,
+    pub output_dir: PathBuf,
+    /// Default permalink pattern, see [`Permalinks`].
+    pub permalink: String,
+    /// Permalink patterns per section.
+    pub permalinks: HashMap<String, String>,
+    /// Frontmatter rules for every page, see [`aaska::schema`].
+    pub schema: Option<Schema>,
+    /// Frontmatter rules per section, replacing `schema`.
+    pub schemas: HashMap<String, Schema>,
+    /// Layout per section, see [`crate::layouts::Layouts`].
+    pub layouts: HashMap<String, String>,
+    /// Number of pages per page of the index and taxonomy listings. Unset lists everything on
+    /// one page.
+    pub paginate: Option<usize>,
+    /// Frontmatter keys whose terms get listing pages, see [`aaska::taxonomy`].
+    pub taxonomies: Vec<String>,
+    /// Glob patterns, relative to `static/`, of files that are not copied.
+    pub static_ignore: Vec<String>,
+    /// Copy every static file and directory whose name starts with a dot.
+    pub static_hidden: bool,
+    /// Names starting with a dot that are copied from `static/` anyway, such as `.well-known`
+    /// or `.htaccess`.
+    pub static_allow_hidden: Vec<String>,
+    /// Glob patterns, relative to the content directory. If any are given, only the matching
+    /// pages and bundle assets are read.
+    pub include: Vec<String>,
+    /// Glob patterns, relative to the content or static directory, of files and directories
+    /// that are skipped altogether.
+    pub exclude: Vec<String>,
+    /// Read content files and directories whose name starts with a dot.
+    pub hidden: bool,
+    pub markdown: MarkdownConfig,
+    pub feeds: FeedConfig,
+    /// Write `sitemap.xml`.
+    pub sitemap: bool,
+    pub robots: RobotsConfig,
+    /// Arbitrary user values, passed through untouched.
+    pub extra: toml::Table,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        SiteConfig {
+            theme: None,
+            title: "Aaska".to_string(),
+            author: String::new(),
+            base_url: None,
+            source_dir: PathBuf::from("content"),
+            output_dir: PathBuf::from("public"),
+            permalink: DEFAULT_PERMALINK.to_string(),
+            permalinks: HashMap::new(),
+            schema: None,
+            schemas: HashMap::new(),
+            layouts: HashMap::new(),
+            paginate: None,
+            taxonomies: vec!["tags".to_string()],
+            static_ignore: Vec::new(),
+            static_hidden: false,
+            static_allow_hidden: Vec::new(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            hidden: false,
+            markdown: MarkdownConfig::default(),
+            feeds: FeedConfig::default(),
+            sitemap: true,
+            robots: RobotsConfig::default(),
+            extra: toml::Table::new(),
+        }
+    }
+}
+
+/// Comrak extension toggles.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MarkdownConfig {
+    pub table: bool,
+    pub strikethrough: bool,
+    pub autolink: bool,
+    pub tasklist: bool,
+    pub footnotes: bool,
+    pub superscript: bool,
+    pub description_lists: bool,
+    pub header_ids: Option<String>,
+    pub smart_punctuation: bool,
+    /// Pass raw HTML in markdown through to the output.
+    pub unsafe_html: bool,
+}
+
+impl Default for MarkdownConfig {
+    fn default() -> Self {
+        MarkdownConfig {
+            table: true,
+            strikethrough: true,
+            autolink: true,
+            tasklist: true,
+            footnotes: true,
+            superscript: false,
+            description_lists: false,
+            header_ids: None,
+            smart_punctuation: false,
+            unsafe_html: false,
+        }
+    }
+}
+
+impl MarkdownConfig {
+    pub fn comrak_options(&self) -> ComrakOptions<'static> {
+        ComrakOptions {
+            extension: ExtensionOptions {
+                table: self.table,
+                strikethrough: self.strikethrough,
+                autolink: self.autolink,
+                tasklist: self.tasklist,
+                footnotes: self.footnotes,
+                superscript: self.superscript,
+                description_lists: self.description_lists,
+                header_ids: self.header_ids.clone(),
+                front_matter_delimiter: Some("---".to_string()),
+                ..Default::default()
+            },
+            parse: ParseOptions {
+                smart: self.smart_punctuation,
+                ..Default::default()
+            },
+            render: RenderOptions {
+                unsafe_: self.unsafe_html,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Which feeds to generate, see [`crate::feeds`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeedConfig {
+    pub rss: bool,
+    pub atom: bool,
+    /// `feed.json`, in the JSON Feed format.
+    pub json: bool,
+    /// Include the rendered page in each entry, not only its summary.
+    pub full_content: bool,
+    /// Maximum number of entries per feed.
+    pub limit: usize,
+    /// Also generate a feed per tag, under `/tags/<tag>/`.
+    pub tags: bool,
+    /// Also generate a feed per section, under `/<section>/`.
+    pub sections: bool,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        FeedConfig {
+            rss: true,
+            atom: true,
+            json: true,
+            full_content: false,
+            limit: 20,
+            tags: false,
+            sections: false,
+        }
+    }
+}
+
+/// Contents of `robots.txt`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RobotsConfig {
+    pub enabled: bool,
+    pub user_agent: String,
+    /// Path prefixes crawlers should stay out of.
+    pub disallow: Vec<String>,
+    /// Appended verbatim, for anything the keys above do not cover.
+    pub extra: String,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        RobotsConfig {
+            enabled: true,
+            user_agent: "*".to_string(),
+            disallow: Vec::new(),
+            extra: String::new(),
+        }
+    }
+}
+
+impl RobotsConfig {
+    /// The `robots.txt` file, pointing at `sitemap_url` if there is a sitemap.
+    pub fn render(&self, sitemap_url: Option<&str>) -> String {
+        let mut robots = format!("User-agent: {}\n", self.user_agent);
+        if self.disallow.is_empty() {
+            robots.push_str("Disallow:\n");
+        }
+        for path in &self.disallow {
+            robots.push_str(&format!("Disallow: {path}\n"));
+        }
+        if let Some(url) = sitemap_url {
+            robots.push_str(&format!("\nSitemap: {url}\n"));
+        }
+        if !self.extra.is_empty() {
+            robots.push('\n');
+            robots.push_str(&self.extra);
+            if !self.extra.ends_with('\n') {
+                robots.push('\n');
+            }
+        }
+        robots
+    }
+}
+
+/// A parsed config file together with the directory it applies to.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    pub site: SiteConfig,
+    pub root: PathBuf,
+    pub theme_dir: Option<PathBuf>,
+}
+
+impl LoadedConfig {
+    /// Load `explicit` if given, otherwise look for `aaska.toml` in the working directory and
+    /// its ancestors. Falls back to the defaults rooted at the working directory.
+    pub fn load(explicit: Option<&Path>) -> Result<LoadedConfig> {
+        let path = match explicit {
+            Some(path) => Some(path.to_path_buf()),
+            None => discover(&std::env::current_dir()?),
+        };
+
+        let Some(path) = path else {
+            debug!("No {CONFIG_FILE_NAME} found, using defaults");
+            return Ok(LoadedConfig {
+                site: SiteConfig::default(),
+                root: std::env::current_dir()?,
+                theme_dir: None,
+            });
+        };
+
+        let contents = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("Failed to read config file: {}", path.display()))?;
+        let table: toml::Table = toml::from_str(&contents)
+            .wrap_err_with(|| format!("Failed to parse config file: {}", path.display()))?;
+        let root = path
+            .canonicalize()
+            .wrap_err_with(|| format!("Failed to resolve config file: {}", path.display()))?
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        info!("Using config file: {}", path.display());
+
+        // The theme provides defaults, which the site file overrides.
+        let theme_dir = match table.get("theme").and_then(toml::Value::as_str) {
+            Some(name) => Some(theme::locate(&root, name)?),
+            None => None,
+        };
+        let table = match theme_dir
+            .as_deref()
+            .map(theme::default_config)
+            .transpose()?
+        {
+            Some(Some(mut defaults)) => {
+                defaults.remove("theme");
+                theme::merge_tables(&mut defaults, table);
+                defaults
+            }
+            _ => table,
+        };
+
+        let site = SiteConfig::deserialize(table)
+            .wrap_err_with(|| format!("Invalid config file: {}", path.display()))?;
+        Ok(LoadedConfig {
+            site,
+            root,
+            theme_dir,
+        })
+    }
+
+    /// Warn if `base_url` is set but not absolute while there is a sitemap, whose URLs search
+    /// engines need absolute. For the commands that publish the site, not for `serve`.
+    pub fn check_base_url(&self) {
+        let Some(base_url) = &self.site.base_url else {
+            return;
+        };
+        if self.site.sitemap && !base_url.contains("://") {
+            warn!(
+                "base_url {base_url:?} is not an absolute URL, so are the URLs in sitemap.xml \
+                 and robots.txt. Set it to where the site is published, like \
+                 \"https://example.com/\""
+            );
+        }
+    }
+
+    /// Build the runtime configuration. `input` and `output` take precedence over the file.
+    pub fn into_config(
+        self,
+        input: Option<PathBuf>,
+        output: Option<PathBuf>,
+    ) -> Result<(crate::Config<'static>, crate::SiteMetadata)> {
+        let config = crate::Config {
+            search_path: SearchPath::new(self.root.clone(), self.theme_dir),
+            source_dir: input.unwrap_or_else(|| self.root.join(&self.site.source_dir)),
+            output_dir: output.unwrap_or_else(|| self.root.join(&self.site.output_dir)),
+            comrak_options: self.site.markdown.comrak_options(),
+            permalinks: Permalinks {
+                default: self.site.permalink,
+                sections: self.site.permalinks,
+            },
+            schemas: Schemas {
+                default: self.site.schema,
+                sections: self.site.schemas,
+            },
+            layouts: self.site.layouts,
+            paginate: self.site.paginate,
+            taxonomies: self.site.taxonomies,
+            static_ignore: glob_set(&self.site.static_ignore)?,
+            static_scan: ScanOptions {
+                hidden: self.site.static_hidden,
+                allow_hidden: self.site.static_allow_hidden,
+                include: Vec::new(),
+                exclude: self.site.exclude.clone(),
+            },
+            content_scan: ScanOptions {
+                hidden: self.site.hidden,
+                allow_hidden: Vec::new(),
+                include: self.site.include,
+                exclude: self.site.exclude,
+            },
+            feeds: self.site.feeds,
+            sitemap: self.site.sitemap,
+            robots: self.site.robots,
+            drafts: false,
+            keep_going: false,
+        };
+        let meta = crate::SiteMetadata {
+            title: self.site.title,
+            author: self.site.author,
+            base_url: self.site.base_url.unwrap_or_else(|| "/".to_string()),
+            extra: self.site.extra,
+        };
+        Ok((config, meta))
+    }
+}
+
+fn glob_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern).wrap_err_with(|| format!("Invalid glob: {pattern}"))?);
+    }
+    Ok(builder.build()?)
+}
+
+fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|candidate| candidate.is_file())
+}
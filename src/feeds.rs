@@ -0,0 +1,143 @@
+//! The site's RSS, Atom and JSON feeds.
+//!
+//! The site feeds live at `/rss.xml`, `/atom.xml` and `/feed.json`. With `tags` or
+//! `sections` enabled in `[feeds]`, the same files are generated under `/tags/<tag>/` and
+//! `/<section>/` for the pages with that tag or in that section.
+
+use std::collections::BTreeMap;
+
+use aaska::{
+    feed::{Feed, FeedEntry},
+    md::ParsedFile,
+    url::slugify,
+};
+
+use crate::prelude::*;
+use crate::{Config, SiteMetadata};
+
+pub const RSS_FILE_NAME: &str = "rss.xml";
+pub const ATOM_FILE_NAME: &str = "atom.xml";
+pub const JSON_FILE_NAME: &str = "feed.json";
+
+/// Every enabled feed, as `(url, contents)` pairs. `pages` must be sorted newest first.
+pub fn render(
+    config: &Config,
+    meta: &SiteMetadata,
+    pages: &[&ParsedFile],
+) -> Result<Vec<(String, String)>> {
+    let feeds = &config.feeds;
+    if !feeds.rss && !feeds.atom && !feeds.json {
+        return Ok(Vec::new());
+    }
+
+    let mut groups: BTreeMap<String, (String, Vec<&ParsedFile>)> = BTreeMap::new();
+    groups.insert("/".to_string(), (meta.title.clone(), pages.to_vec()));
+    for &page in pages {
+        if feeds.sections && !page.meta.section().is_empty() {
+            let section = page.meta.section();
+            groups
+                .entry(format!("/{}/", slugify(section)))
+                .or_insert_with(|| (format!("{} - {section}", meta.title), Vec::new()))
+                .1
+                .push(page);
+        }
+        if feeds.tags {
+            let tags = page
+                .contents
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.tags.as_deref())
+                .unwrap_or(&[]);
+            for tag in tags {
+                groups
+                    .entry(format!("/tags/{}/", slugify(tag)))
+                    .or_insert_with(|| (format!("{} - {tag}", meta.title), Vec::new()))
+                    .1
+                    .push(page);
+            }
+        }
+    }
+
+    let mut rendered = Vec::new();
+    for (link, (title, pages)) in groups {
+        let entries = pages
+            .into_iter()
+            .take(feeds.limit)
+            .map(|page| {
+                let content = if feeds.full_content {
+                    let html = aaska::html::generate_html(page, &config.comrak_options)?;
+                    Some(html.contents.0)
+                } else {
+                    None
+                };
+                Ok(FeedEntry::new(page, content))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let feed = |self_url: String| Feed {
+            title: title.clone(),
+            description: meta.title.clone(),
+            author: meta.author.clone(),
+            base_url: meta.base_url.clone(),
+            link: link.clone(),
+            self_url,
+            entries: entries.clone(),
+        };
+
+        if feeds.rss {
+            let url = format!("{link}{RSS_FILE_NAME}");
+            rendered.push((url.clone(), feed(url).rss()));
+        }
+        if feeds.atom {
+            let url = format!("{link}{ATOM_FILE_NAME}");
+            rendered.push((url.clone(), feed(url).atom()));
+        }
+        if feeds.json {
+            let url = format!("{link}{JSON_FILE_NAME}");
+            rendered.push((url.clone(), feed(url).json()));
+        }
+    }
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use aaska::{comrak::Arena, md::MarkdownParser};
+
+    use super::*;
+    use crate::config::{LoadedConfig, SiteConfig};
+
+    #[test]
+    fn test_render_default() {
+        let loaded = LoadedConfig {
+            site: SiteConfig::default(),
+            root: PathBuf::from("/site"),
+            theme_dir: None,
+        };
+        let (config, meta) = loaded.into_config(None, None).unwrap();
+
+        let arena = Arena::new();
+        let parser = MarkdownParser::with_arena(&arena, &config.comrak_options);
+        let page = parser.test_page(
+            "hello.md",
+            "---\ntitle: Hello\n---\nFirst *paragraph* & more.\n\nSecond.",
+        );
+
+        let feeds = render(&config, &meta, &[&page]).unwrap();
+        let urls: Vec<_> = feeds.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(urls, ["/rss.xml", "/atom.xml", "/feed.json"]);
+
+        // Without `full_content`, entries carry the plain text summary only.
+        let json = &feeds[2].1;
+        assert!(!json.contains("content_html"), "{json}");
+        assert!(
+            json.contains(r#""content_text": "First paragraph & more.""#),
+            "{json}"
+        );
+        assert!(
+            json.contains(r#""summary": "First paragraph & more.""#),
+            "{json}"
+        );
+    }
+}
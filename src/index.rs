@@ -1,16 +1,11 @@
-use aaska::md::PageList;
+use aaska::{md::ParsedFile, paginate::Pager};
 
-pub fn index_html<'c>(meta: crate::SiteMetadata, post_list: &'c PageList<'c>) -> String {
-    let page_links = post_list
-        .sorted_by_date()
+pub fn index_html(meta: &crate::SiteMetadata, pager: &Pager<&ParsedFile>) -> String {
+    let page_links = pager
+        .items
         .iter()
         .map(|file| {
-            let title = file
-                .contents
-                .frontmatter
-                .as_ref()
-                .and_then(|fm| fm.title.clone())
-                .unwrap_or("untitled".to_string());
+            let title = file.title();
 
             let date = file
                 .contents
@@ -20,40 +15,33 @@ pub fn index_html<'c>(meta: crate::SiteMetadata, post_list: &'c PageList<'c>) ->
                 .unwrap_or("unknown date".to_string());
 
             format!(
-                "<li><a href=\"/posts/{}\">{}</a> - <em>{}</em></li>",
-                file.meta.path.file_name().unwrap().to_str().unwrap(),
-                title,
-                date
+                "<li><a href=\"{}\">{}</a> - <em>{}</em></li>",
+                file.meta.url, title, date
             )
         })
         .collect::<Vec<_>>()
         .join("\n");
 
-    maud::html! {
-        html {
-            head {
-                title { "Aaska" }
-                link rel="stylesheet" href="/static/style.css" {}
-            }
-            body {
-                h1 { "Welcome" }
-                p { "This is the index page." }
-                p { "Author: " (meta.author) }
+    crate::layouts::base(
+        meta,
+        "Home",
+        maud::html! {
+            h1 { (meta.title) }
+            p { "This is the index page." }
+            p { "Author: " (meta.author) }
 
-                div {
-                    h2 { "Recent Posts" }
-                    ul {
-                        @if page_links.is_empty() {
-                            li { "No posts available." }
-                        } @else {
-                            (maud::PreEscaped(page_links))
-                        }
+            div {
+                h2 { "Recent Posts" }
+                ul {
+                    @if page_links.is_empty() {
+                        li { "No posts available." }
+                    } @else {
+                        (maud::PreEscaped(page_links))
                     }
                 }
-
-
+                (crate::layouts::pagination(pager))
             }
-        }
-    }
+        },
+    )
     .0
 }
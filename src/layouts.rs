@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use aaska::{
+    md::{FrontmatterData, PageList, ParsedFile},
+    paginate::Pager,
+    taxonomy::{Taxonomy, Term},
+};
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+
+use crate::prelude::*;
+use crate::theme::SearchPath;
+use crate::SiteMetadata;
+
+pub const DEFAULT_LAYOUT: &str = "default";
+
+/// Everything a layout gets to render a page with.
+pub struct LayoutContext<'a, 'c> {
+    pub page: &'a ParsedFile<'c>,
+    /// The rendered page body.
+    pub body: &'a str,
+    pub site: &'a SiteMetadata,
+    pub pages: &'a PageList<'c>,
+}
+
+impl<'a, 'c> LayoutContext<'a, 'c> {
+    pub fn frontmatter(&self) -> Option<&FrontmatterData> {
+        self.page.contents.frontmatter.as_ref()
+    }
+
+    pub fn title(&self) -> &str {
+        self.page.title()
+    }
+
+    /// The previous and next page by date within the same section.
+    pub fn neighbours(&self) -> (Option<&'a ParsedFile<'c>>, Option<&'a ParsedFile<'c>>) {
+        let section = self.page.meta.section();
+        let siblings: Vec<_> = self
+            .pages
+            .sorted_by_date()
+            .into_iter()
+            .filter(|p| p.meta.section() == section)
+            .collect();
+        let Some(pos) = siblings
+            .iter()
+            .position(|p| p.meta.path == self.page.meta.path)
+        else {
+            return (None, None);
+        };
+        let prev = pos.checked_sub(1).map(|i| siblings[i]);
+        (prev, siblings.get(pos + 1).copied())
+    }
+}
+
+pub type Layout = fn(&LayoutContext) -> Markup;
+
+/// The available layouts, and which one each page uses.
+///
+/// A page picks its layout from the `layout` frontmatter key, then from the layout configured
+/// for its section, then falls back to [`DEFAULT_LAYOUT`].
+///
+/// With the `templates` feature, a template of the same name in the site's `templates/`
+/// directory takes precedence over the built-in layout.
+pub struct Layouts {
+    layouts: HashMap<&'static str, Layout>,
+    sections: HashMap<String, String>,
+    #[cfg(feature = "templates")]
+    templates: Option<crate::templates::Templates>,
+}
+
+impl Layouts {
+    #[cfg_attr(not(feature = "templates"), allow(unused_variables))]
+    pub fn new(sections: HashMap<String, String>, search_path: &SearchPath) -> Self {
+        let mut layouts: HashMap<&'static str, Layout> = HashMap::new();
+        layouts.insert(DEFAULT_LAYOUT, default_layout);
+        layouts.insert("post", post_layout);
+        Layouts {
+            layouts,
+            sections,
+            #[cfg(feature = "templates")]
+            templates: crate::templates::Templates::load(search_path),
+        }
+    }
+
+    pub fn layout_name<'a>(&'a self, page: &'a ParsedFile) -> &'a str {
+        page.contents
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.layout.as_deref())
+            .or_else(|| self.sections.get(page.meta.section()).map(String::as_str))
+            .unwrap_or(DEFAULT_LAYOUT)
+    }
+
+    pub fn render(&self, ctx: &LayoutContext) -> Result<String> {
+        let name = self.layout_name(ctx.page);
+
+        #[cfg(feature = "templates")]
+        if let Some(templates) = &self.templates {
+            if let Some(html) = templates.render_layout(name, ctx)? {
+                return Ok(html);
+            }
+        }
+
+        let layout = self.layouts.get(name).ok_or_else(|| {
+            color_eyre::eyre::eyre!(
+                "Unknown layout {name:?} for {}",
+                ctx.page.meta.path.display()
+            )
+        })?;
+        Ok(layout(ctx).into_string())
+    }
+
+    /// Render one page of the index.
+    pub fn render_index(&self, site: &SiteMetadata, pager: &Pager<&ParsedFile>) -> Result<String> {
+        #[cfg(feature = "templates")]
+        if let Some(templates) = &self.templates {
+            if let Some(html) = templates.render_index(site, pager)? {
+                return Ok(html);
+            }
+        }
+
+        Ok(crate::index::index_html(site, pager))
+    }
+
+    /// Render the page listing every term of `taxonomy`.
+    pub fn render_taxonomy(&self, site: &SiteMetadata, taxonomy: &Taxonomy) -> Result<String> {
+        #[cfg(feature = "templates")]
+        if let Some(templates) = &self.templates {
+            if let Some(html) = templates.render_taxonomy(site, taxonomy)? {
+                return Ok(html);
+            }
+        }
+
+        Ok(crate::taxonomy::taxonomy_html(site, taxonomy).into_string())
+    }
+
+    /// Render one page of the listing of the pages with `term`.
+    pub fn render_term(
+        &self,
+        site: &SiteMetadata,
+        taxonomy: &Taxonomy,
+        term: &Term,
+        pager: &Pager<&ParsedFile>,
+    ) -> Result<String> {
+        #[cfg(feature = "templates")]
+        if let Some(templates) = &self.templates {
+            if let Some(html) = templates.render_term(site, taxonomy, term, pager)? {
+                return Ok(html);
+            }
+        }
+
+        Ok(crate::taxonomy::term_html(site, taxonomy, term, pager).into_string())
+    }
+}
+
+/// The chrome shared by every page.
+pub fn base(site: &SiteMetadata, title: &str, content: Markup) -> Markup {
+    html! {
+        (DOCTYPE)
+        html {
+            head {
+                meta charset="utf-8";
+                meta name="viewport" content="width=device-width, initial-scale=1";
+                title { (title) " | " (site.title) }
+                link rel="stylesheet" href="/static/style.css";
+            }
+            body {
+                header {
+                    nav { a href="/" { (site.title) } }
+                }
+                main { (content) }
+                footer {
+                    @if !site.author.is_empty() {
+                        p { "© " (site.author) }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Links to the other pages of a paginated listing, or nothing if it fits on one page.
+pub fn pagination<T>(pager: &Pager<T>) -> Markup {
+    html! {
+        @if pager.total > 1 {
+            nav class="pagination" {
+                a rel="first" href=(pager.first) { "« First" }
+                @if let Some(prev) = &pager.prev {
+                    a rel="prev" href=(prev) { "‹ Previous" }
+                }
+                span { "Page " (pager.number) " of " (pager.total) }
+                @if let Some(next) = &pager.next {
+                    a rel="next" href=(next) { "Next ›" }
+                }
+                a rel="last" href=(pager.last) { "Last »" }
+            }
+        }
+    }
+}
+
+fn default_layout(ctx: &LayoutContext) -> Markup {
+    base(
+        ctx.site,
+        ctx.title(),
+        html! {
+            article { (PreEscaped(ctx.body)) }
+        },
+    )
+}
+
+fn post_layout(ctx: &LayoutContext) -> Markup {
+    let frontmatter = ctx.frontmatter();
+    let date = frontmatter.and_then(|fm| fm.date);
+    let tags = frontmatter.and_then(|fm| fm.tags.as_deref()).unwrap_or(&[]);
+    let (prev, next) = ctx.neighbours();
+
+    base(
+        ctx.site,
+        ctx.title(),
+        html! {
+            article {
+                header {
+                    h1 { (ctx.title()) }
+                    @if let Some(date) = date {
+                        time datetime=(date) { (date) }
+                    }
+                    @if !tags.is_empty() {
+                        ul class="tags" {
+                            @for tag in tags { li { (tag) } }
+                        }
+                    }
+                }
+                (PreEscaped(ctx.body))
+            }
+            nav class="pagination" {
+                @if let Some(prev) = prev {
+                    a rel="prev" href=(prev.meta.url) { "← " (prev.title()) }
+                }
+                @if let Some(next) = next {
+                    a rel="next" href=(next.meta.url) { (next.title()) " →" }
+                }
+            }
+        },
+    )
+}
@@ -3,23 +3,63 @@ mod prelude {
     pub use color_eyre::eyre::{Result, WrapErr};
     pub use tracing::{debug, error, info, instrument, span, trace, warn};
 }
-use aaska::comrak::ComrakOptions;
+use aaska::{comrak::ComrakOptions, fs::ScanOptions, schema::Schemas, url::Permalinks};
+use config::{FeedConfig, RobotsConfig};
+use globset::GlobSet;
 use prelude::*;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::{collections::HashMap, path::PathBuf};
+use theme::SearchPath;
 
+mod build;
 mod cli;
+mod config;
+mod feeds;
 mod index;
+mod layouts;
+mod taxonomy;
+#[cfg(feature = "templates")]
+mod templates;
+mod theme;
+mod watcher;
 
 mod cmds;
 
 struct Config<'c> {
+    /// Where templates and static files are looked up: the site directory, then its theme.
+    pub search_path: SearchPath,
     pub source_dir: PathBuf,
     pub output_dir: PathBuf,
     pub comrak_options: ComrakOptions<'c>,
+    pub permalinks: Permalinks,
+    pub schemas: Schemas,
+    /// Layout name per section.
+    pub layouts: HashMap<String, String>,
+    /// Entries per page of paginated listings.
+    pub paginate: Option<usize>,
+    pub taxonomies: Vec<String>,
+    /// Files under `static/` that are not copied.
+    pub static_ignore: GlobSet,
+    /// Which content files are read at all.
+    pub content_scan: ScanOptions,
+    /// Which static files are considered for copying, before `static_ignore`.
+    pub static_scan: ScanOptions,
+    pub feeds: FeedConfig,
+    pub sitemap: bool,
+    pub robots: RobotsConfig,
+    /// Build drafts and pages whose `publish_date` is still ahead. Set from the command line.
+    pub drafts: bool,
+    /// Skip files that fail to parse or render instead of stopping the build, see
+    /// [`build::BuildReport::check`]. Set from the command line.
+    pub keep_going: bool,
 }
 
+#[derive(Serialize)]
 pub struct SiteMetadata {
-    pub author: &'static str,
+    pub title: String,
+    pub author: String,
+    pub base_url: String,
+    pub extra: toml::Table,
 }
 
 fn validate_config(config: &Config) -> Result<()> {
@@ -35,14 +75,44 @@ fn validate_config(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Resolve both directories to absolute paths, so they compare equal to the paths reported
+/// by the file watcher. Must run after [`validate_config`] created the output directory.
+fn canonicalize_dirs(config: &mut Config) -> Result<()> {
+    config.source_dir = config.source_dir.canonicalize().wrap_err_with(|| {
+        format!(
+            "Source directory not found: {}",
+            config.source_dir.display()
+        )
+    })?;
+    config.output_dir = config.output_dir.canonicalize()?;
+    Ok(())
+}
+
+/// Format `report` for the terminal. Problems in source files are shown as annotated snippets
+/// rather than an error chain.
+pub fn render_error(report: &color_eyre::Report) -> String {
+    if let Some(failures) = report.downcast_ref::<build::BuildFailures>() {
+        return failures.to_string();
+    }
+    match report.downcast_ref::<aaska::Error>() {
+        Some(aaska::Error::Parse(diagnostics)) => diagnostics.to_string(),
+        _ => format!("{report:?}"),
+    }
+}
+
 fn main() {
     color_eyre::install().expect("Failed to install color_eyre");
     let args = cli::ParsedArgs::parse_raw();
     let _guard = argus::tracing::setup_tracing(&args.tracing_options);
 
-    match args.command {
-        cli::Command::Generate(args) => cmds::generate::generate(args),
+    let result = match args.command {
+        cli::Command::Generate(gen_args) => cmds::generate::generate(args.config_path, gen_args),
+        cli::Command::Watch(gen_args) => cmds::watch::watch(args.config_path, gen_args),
+        cli::Command::Serve(serve_args) => cmds::serve::serve(args.config_path, serve_args),
         cli::Command::Sample => cmds::sample::generate_sample_source(),
+    };
+    if let Err(report) = result {
+        eprintln!("{}", render_error(&report));
+        std::process::exit(1);
     }
-    .expect("Failed to execute command");
 }
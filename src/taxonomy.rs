@@ -0,0 +1,54 @@
+//! Built-in pages for taxonomies, see [`aaska::taxonomy`].
+
+use aaska::{
+    md::ParsedFile,
+    paginate::Pager,
+    taxonomy::{Taxonomy, Term},
+};
+use maud::{html, Markup};
+
+use crate::SiteMetadata;
+
+pub fn taxonomy_html(site: &SiteMetadata, taxonomy: &Taxonomy) -> Markup {
+    crate::layouts::base(
+        site,
+        &taxonomy.name,
+        html! {
+            h1 { (taxonomy.name) }
+            ul class="terms" {
+                @for term in &taxonomy.terms {
+                    li {
+                        a href=(term.url) { (term.name) }
+                        " (" (term.pages.len()) ")"
+                    }
+                }
+            }
+        },
+    )
+}
+
+pub fn term_html(
+    site: &SiteMetadata,
+    taxonomy: &Taxonomy,
+    term: &Term,
+    pager: &Pager<&ParsedFile>,
+) -> Markup {
+    crate::layouts::base(
+        site,
+        &term.name,
+        html! {
+            h1 { (term.name) }
+            p { a href=(taxonomy.url()) { "All " (taxonomy.name) } }
+            ul {
+                @for page in pager.items {
+                    li {
+                        a href=(page.meta.url) { (page.title()) }
+                        " - "
+                        em { (page.date().date_naive()) }
+                    }
+                }
+            }
+            (crate::layouts::pagination(pager))
+        },
+    )
+}
@@ -0,0 +1,239 @@
+//! Runtime templates, loaded from `templates/*.html` in the site directory.
+//!
+//! Templates are rendered with [minijinja](https://docs.rs/minijinja), so `extends`,
+//! `include` and macros work as usual. Templates of the site's theme are used where the site
+//! has none of its own. A layout named `post` is looked up as `post.html`, and
+//! the index page as `index.html`. Layouts without a template fall back to the built-in maud
+//! ones. Taxonomies use `taxonomy.html` for the list of terms and `term.html` for the pages
+//! of one term.
+//!
+//! Every template gets:
+//!
+//! - `page`: `title`, `url`, `date`, `tags`, `section`, `path`, `frontmatter`, `content`, and
+//!   `extra` with the frontmatter keys aaska does not know about
+//! - `site`: the `title`, `author`, `base_url` and `extra` values from the config
+//! - `pages`: every page sorted by date, as `page` but without `content`
+//!
+//! Taxonomy templates get `site`, and `taxonomy` with its `name`, `url` and `terms`. Each term
+//! has a `name`, `slug`, `url` and page `count`. `term.html` also gets the `term` itself, and
+//! its `pages`, newest first.
+//!
+//! `index.html` gets its `pages` newest first. It and `term.html` only get the `pages` of the
+//! current page when `paginate` is set, along with a `paginator`: the page `number`, the
+//! `total` number of pages, and the `first`, `last`, `prev` and `next` URLs (the latter two
+//! may be missing).
+
+use std::path::Path;
+
+use aaska::{
+    md::{Extra, FrontmatterData, ParsedFile},
+    paginate::Pager,
+    taxonomy::{Taxonomy, Term},
+};
+use minijinja::{context, Environment, ErrorKind, Value};
+use serde::Serialize;
+
+use crate::layouts::LayoutContext;
+use crate::prelude::*;
+use crate::theme::SearchPath;
+use crate::SiteMetadata;
+
+pub const TEMPLATES_DIR: &str = "templates";
+
+pub struct Templates {
+    env: Environment<'static>,
+}
+
+impl Templates {
+    /// Load templates from the `templates` directories on `search_path`, or `None` if there
+    /// are none.
+    pub fn load(search_path: &SearchPath) -> Option<Templates> {
+        if !search_path.has_dir(Path::new(TEMPLATES_DIR)) {
+            return None;
+        }
+
+        let search_path = search_path.clone();
+        let mut env = Environment::new();
+        env.set_loader(move |name: &str| {
+            let Some(path) = search_path.resolve(&Path::new(TEMPLATES_DIR).join(name)) else {
+                return Ok(None);
+            };
+            std::fs::read_to_string(&path).map(Some).map_err(|e| {
+                minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("Failed to read template {}", path.display()),
+                )
+                .with_source(e)
+            })
+        });
+        Some(Templates { env })
+    }
+
+    /// Render `layout.html`, or `None` if there is no such template.
+    pub fn render_layout(&self, layout: &str, ctx: &LayoutContext) -> Result<Option<String>> {
+        let pages: Vec<_> = ctx
+            .pages
+            .sorted_by_date()
+            .into_iter()
+            .map(PageValue::new)
+            .collect();
+        let page = PageValue {
+            content: Some(Value::from_safe_string(ctx.body.to_string())),
+            ..PageValue::new(ctx.page)
+        };
+
+        self.render(
+            &format!("{layout}.html"),
+            context! { page, site => ctx.site, pages },
+        )
+        .wrap_err_with(|| format!("Failed to render {}", ctx.page.meta.path.display()))
+    }
+
+    /// Render `index.html`, or `None` if there is no such template.
+    pub fn render_index(
+        &self,
+        site: &SiteMetadata,
+        pager: &Pager<&ParsedFile>,
+    ) -> Result<Option<String>> {
+        let pages: Vec<_> = pager.items.iter().copied().map(PageValue::new).collect();
+        let paginator = PaginatorValue::new(pager);
+        self.render("index.html", context! { site, pages, paginator })
+            .wrap_err("Failed to render the index page")
+    }
+
+    /// Render `taxonomy.html`, or `None` if there is no such template.
+    pub fn render_taxonomy(
+        &self,
+        site: &SiteMetadata,
+        taxonomy: &Taxonomy,
+    ) -> Result<Option<String>> {
+        let taxonomy = TaxonomyValue::new(taxonomy);
+        self.render("taxonomy.html", context! { site, taxonomy })
+            .wrap_err_with(|| format!("Failed to render the {} page", taxonomy.name))
+    }
+
+    /// Render `term.html`, or `None` if there is no such template.
+    pub fn render_term(
+        &self,
+        site: &SiteMetadata,
+        taxonomy: &Taxonomy,
+        term: &Term,
+        pager: &Pager<&ParsedFile>,
+    ) -> Result<Option<String>> {
+        let pages: Vec<_> = pager.items.iter().copied().map(PageValue::new).collect();
+        self.render(
+            "term.html",
+            context! {
+                site,
+                taxonomy => TaxonomyValue::new(taxonomy),
+                term => TermValue::new(term),
+                pages,
+                paginator => PaginatorValue::new(pager),
+            },
+        )
+        .wrap_err_with(|| format!("Failed to render the page for {}", term.url))
+    }
+
+    fn render(&self, name: &str, ctx: Value) -> Result<Option<String>, minijinja::Error> {
+        let template = match self.env.get_template(name) {
+            Ok(template) => template,
+            Err(e) if e.kind() == ErrorKind::TemplateNotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        template.render(ctx).map(Some)
+    }
+}
+
+#[derive(Serialize)]
+struct PageValue<'a> {
+    title: &'a str,
+    url: &'a str,
+    date: String,
+    tags: &'a [String],
+    section: &'a str,
+    path: String,
+    frontmatter: Option<&'a FrontmatterData>,
+    extra: &'a Extra,
+    content: Option<Value>,
+}
+
+static NO_EXTRA: Extra = Extra::new();
+
+impl<'a> PageValue<'a> {
+    fn new(page: &'a ParsedFile) -> Self {
+        let frontmatter = page.contents.frontmatter.as_ref();
+        PageValue {
+            title: page.title(),
+            url: &page.meta.url,
+            date: frontmatter
+                .and_then(|fm| fm.date)
+                .unwrap_or_else(|| page.meta.date.date_naive())
+                .to_string(),
+            tags: frontmatter.and_then(|fm| fm.tags.as_deref()).unwrap_or(&[]),
+            section: page.meta.section(),
+            path: page.meta.rel_path.to_string_lossy().into_owned(),
+            frontmatter,
+            extra: frontmatter.map_or(&NO_EXTRA, |fm| &fm.extra),
+            content: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TaxonomyValue<'a> {
+    name: &'a str,
+    url: String,
+    terms: Vec<TermValue<'a>>,
+}
+
+impl<'a> TaxonomyValue<'a> {
+    fn new(taxonomy: &'a Taxonomy) -> Self {
+        TaxonomyValue {
+            name: &taxonomy.name,
+            url: taxonomy.url(),
+            terms: taxonomy.terms.iter().map(TermValue::new).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct TermValue<'a> {
+    name: &'a str,
+    slug: &'a str,
+    url: &'a str,
+    count: usize,
+}
+
+impl<'a> TermValue<'a> {
+    fn new(term: &'a Term) -> Self {
+        TermValue {
+            name: &term.name,
+            slug: &term.slug,
+            url: &term.url,
+            count: term.pages.len(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PaginatorValue<'a> {
+    number: usize,
+    total: usize,
+    first: &'a str,
+    last: &'a str,
+    prev: Option<&'a str>,
+    next: Option<&'a str>,
+}
+
+impl<'a> PaginatorValue<'a> {
+    fn new<T>(pager: &'a Pager<T>) -> Self {
+        PaginatorValue {
+            number: pager.number,
+            total: pager.total,
+            first: &pager.first,
+            last: &pager.last,
+            prev: pager.prev.as_deref(),
+            next: pager.next.as_deref(),
+        }
+    }
+}
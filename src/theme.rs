@@ -0,0 +1,110 @@
+//! Themes: a directory with `templates/`, `static/` and a default `theme.toml`, shared between
+//! sites.
+//!
+//! A site selects one with `theme = "name"`, which is looked up in `<site>/themes/<name>`.
+//! Site files shadow theme files path by path, so a site can override a single partial or
+//! stylesheet and inherit the rest.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use aaska::fs::ScanOptions;
+
+use crate::prelude::*;
+
+pub const THEMES_DIR: &str = "themes";
+pub const THEME_CONFIG_FILE_NAME: &str = "theme.toml";
+
+/// Locate the theme `name` for the site in `root`.
+pub fn locate(root: &Path, name: &str) -> Result<PathBuf> {
+    let dir = root.join(THEMES_DIR).join(name);
+    if !dir.is_dir() {
+        return Err(color_eyre::eyre::eyre!(
+            "Theme {name:?} not found, expected a directory at: {}",
+            dir.display()
+        ));
+    }
+    Ok(dir)
+}
+
+/// Read the theme's default config, if it has one.
+pub fn default_config(theme_dir: &Path) -> Result<Option<toml::Table>> {
+    let path = theme_dir.join(THEME_CONFIG_FILE_NAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .wrap_err_with(|| format!("Failed to read theme config: {}", path.display()))?;
+    let table = toml::from_str(&contents)
+        .wrap_err_with(|| format!("Failed to parse theme config: {}", path.display()))?;
+    Ok(Some(table))
+}
+
+/// The directories site files are looked up in, most specific first: the site itself, then
+/// its theme.
+#[derive(Debug, Clone)]
+pub struct SearchPath {
+    dirs: Vec<PathBuf>,
+}
+
+impl SearchPath {
+    pub fn new(root: PathBuf, theme: Option<PathBuf>) -> Self {
+        SearchPath {
+            dirs: std::iter::once(root).chain(theme).collect(),
+        }
+    }
+
+    /// The existing `rel_path` subdirectories, most specific first.
+    pub fn existing_dirs(&self, rel_path: &Path) -> Vec<PathBuf> {
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(rel_path))
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+
+    /// Whether any directory on the path has a `rel_path` subdirectory.
+    #[cfg(feature = "templates")]
+    pub fn has_dir(&self, rel_path: &Path) -> bool {
+        self.dirs.iter().any(|dir| dir.join(rel_path).is_dir())
+    }
+
+    /// The first existing file at `rel_path`.
+    #[cfg(feature = "templates")]
+    pub fn resolve(&self, rel_path: &Path) -> Option<PathBuf> {
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(rel_path))
+            .find(|path| path.is_file())
+    }
+
+    /// Every file under `subdir` across the search path that `scan` lets through, keyed by
+    /// path relative to `subdir`. Where several directories provide the same path, the most
+    /// specific one wins.
+    pub fn files(&self, subdir: &Path, scan: &ScanOptions) -> Result<BTreeMap<PathBuf, PathBuf>> {
+        let mut files = BTreeMap::new();
+        for dir in self.existing_dirs(subdir).into_iter().rev() {
+            for file in aaska::fs::list_files_dir_rec_with(&dir, scan)? {
+                files.insert(file.rel_path, file.path);
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Merge `overrides` into `base`, recursing into tables so that a site only needs to set the
+/// keys it changes.
+pub fn merge_tables(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge_tables(base, overrides)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
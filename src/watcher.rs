@@ -0,0 +1,73 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::prelude::*;
+
+/// Editors tend to emit several events per save; wait this long for the burst to settle.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Block forever, calling `on_change` with the paths that changed under `dirs`.
+///
+/// Changes under `ignore` (typically the output directory) are dropped. Errors returned by
+/// `on_change` are logged and do not stop the watcher.
+pub fn watch(
+    dirs: &[PathBuf],
+    ignore: &Path,
+    mut on_change: impl FnMut(Vec<PathBuf>) -> Result<()>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).wrap_err("Failed to create watcher")?;
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .wrap_err_with(|| format!("Failed to watch directory: {}", dir.display()))?;
+        info!("Watching {} for changes", dir.display());
+    }
+
+    while let Ok(event) = rx.recv() {
+        let mut changed = Vec::new();
+        collect(event, ignore, &mut changed);
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            collect(event, ignore, &mut changed);
+        }
+
+        changed.sort();
+        changed.dedup();
+        if changed.is_empty() {
+            continue;
+        }
+
+        debug!("Changed: {changed:?}");
+        if let Err(e) = on_change(changed) {
+            error!("{}", crate::render_error(&e));
+        }
+    }
+
+    Ok(())
+}
+
+fn collect(event: notify::Result<notify::Event>, ignore: &Path, acc: &mut Vec<PathBuf>) {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            warn!("Watch error: {e}");
+            return;
+        }
+    };
+
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+
+    acc.extend(
+        event
+            .paths
+            .into_iter()
+            .filter(|path| !path.starts_with(ignore)),
+    );
+}